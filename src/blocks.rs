@@ -0,0 +1,942 @@
+//! Maintenance of the generated blocks within a plugin's Zsh sources.
+//!
+//! Code that is derived from the plugin's declarations, such as the checks for the
+//! plugins it depends on, is rendered between a pair of marker comments, for example:
+//!
+//! ```zsh
+//! # zsh-plugin:begin:dependencies
+//! @zplugins_declare_plugin_dependencies my_plugin shlog
+//! # zsh-plugin:end:dependencies
+//! ```
+//!
+//! Everything between the markers belongs to this tool and is replaced whenever
+//! the declarations change, leaving the rest of the file untouched. Blocks that are
+//! not derived from a declaration, such as the plugin's hook functions, are only
+//! ever appended to, so their content may be edited. The content of each block is
+//! rendered by the functions in this module, both when a plugin is first generated
+//! and when it is later updated.
+
+use crate::{
+    environment::EnvVar,
+    error::Error,
+    install::PluginManager,
+    requirements::{CommandRequirement, OnMissing},
+    settings::{Setting, SettingType},
+    shell_hooks::ShellHook,
+    widgets::Widget,
+};
+use std::{
+    fmt::Write,
+    fs::{read_to_string, write},
+    path::{Path, PathBuf},
+};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) const B_DEPENDENCIES: &str = "dependencies";
+pub(crate) const B_ENV_RESTORE: &str = "env-restore";
+pub(crate) const B_ENV_SAVE: &str = "env-save";
+pub(crate) const B_HOOK_FUNCTIONS: &str = "hook-functions";
+pub(crate) const B_HOOKS_ADD: &str = "hooks-add";
+pub(crate) const B_HOOKS_REMOVE: &str = "hooks-remove";
+pub(crate) const B_MODULES: &str = "modules";
+pub(crate) const B_REQUIREMENTS: &str = "requirements";
+pub(crate) const B_SETTING_FUNCTIONS: &str = "setting-functions";
+pub(crate) const B_WIDGET_FUNCTIONS: &str = "widget-functions";
+pub(crate) const B_WIDGETS_ADD: &str = "widgets-add";
+pub(crate) const B_WIDGETS_REMOVE: &str = "widgets-remove";
+
+/// Every block of a plugin file generated by this version of the tool.
+pub(crate) const PLUGIN_FILE_BLOCKS: [&str; 12] = [
+    B_DEPENDENCIES,
+    B_REQUIREMENTS,
+    B_MODULES,
+    B_ENV_SAVE,
+    B_HOOKS_ADD,
+    B_WIDGETS_ADD,
+    B_HOOKS_REMOVE,
+    B_WIDGETS_REMOVE,
+    B_ENV_RESTORE,
+    B_HOOK_FUNCTIONS,
+    B_WIDGET_FUNCTIONS,
+    B_SETTING_FUNCTIONS,
+];
+
+/// Render the `dependencies` block of the plugin file, at the top level of the file.
+///
+/// With zplugins the dependencies are declared to the plugin manager, otherwise
+/// the plugin stops loading unless the plugin hash of each dependency is defined.
+pub(crate) fn dependencies_block(
+    plugin_name: &str,
+    display_name: &str,
+    uses_zplugins: bool,
+    dependencies: &[String],
+) -> String {
+    let mut block = String::new();
+    if uses_zplugins {
+        if !dependencies.is_empty() {
+            let _ = writeln!(
+                block,
+                "@zplugins_declare_plugin_dependencies {plugin_name} {}",
+                dependencies.join(" ")
+            );
+        }
+    } else {
+        for dependency in dependencies {
+            let var = dependency.replace('-', "_").to_ascii_uppercase();
+            let _ = writeln!(block, "if (( ! ${{+{var}}} )); then");
+            let _ = writeln!(
+                block,
+                "    print -u2 \"{display_name}: the plugin '{dependency}' must be loaded first\""
+            );
+            let _ = writeln!(block, "    return 1");
+            let _ = writeln!(block, "fi");
+        }
+    }
+    block
+}
+
+/// Render the `requirements` block of the plugin's `_plugin_init` function.
+pub(crate) fn requirements_block(
+    display_name: &str,
+    requirements: &[CommandRequirement],
+) -> String {
+    let mut block = String::new();
+    if requirements.iter().any(|r| r.min_version().is_some()) {
+        let _ = writeln!(block, "    autoload -Uz is-at-least");
+    }
+    for requirement in requirements {
+        let name = requirement.name();
+        let on_failure = |block: &mut String, message: &str| match requirement.on_missing() {
+            OnMissing::Warn => {
+                let _ = writeln!(
+                    block,
+                    "        print -u2 \"{display_name}: warning, {message}\""
+                );
+            }
+            OnMissing::Skip => {
+                let _ = writeln!(block, "        return 1");
+            }
+            OnMissing::Error => {
+                let _ = writeln!(block, "        print -u2 \"{display_name}: {message}\"");
+                let _ = writeln!(block, "        return 1");
+            }
+        };
+        let _ = writeln!(block, "    if (( ! $+commands[{name}] )); then");
+        on_failure(&mut block, &format!("the command '{name}' was not found"));
+        if let Some(version) = requirement.min_version() {
+            let _ = writeln!(
+                block,
+                "    elif [[ \"$(command {name} --version 2>&1)\" =~ '[0-9]+(\\.[0-9]+)+' ]] \\"
+            );
+            let _ = writeln!(
+                block,
+                "        && ! is-at-least {version} \"${{MATCH}}\"; then"
+            );
+            on_failure(
+                &mut block,
+                &format!(
+                    "the command '{name}' is version ${{MATCH}}, {version} or later is required"
+                ),
+            );
+        }
+        let _ = writeln!(block, "    fi");
+    }
+    block
+}
+
+/// Render the `modules` block of the plugin's `_plugin_init` function.
+///
+/// Each module in the `modules` directory is sourced, in lexical order, and the
+/// functions it defines are remembered so that unloading the plugin removes them.
+pub(crate) fn modules_block(plugin_name: &str, plugin_var: &str, uses_zplugins: bool) -> String {
+    let mut block = String::new();
+    let (indent, modules_dir) = if uses_zplugins {
+        ("    ", format!("${{{plugin_var}_PLUGIN_PATH:h}}/modules"))
+    } else {
+        let _ = writeln!(
+            block,
+            "    if [[ -d \"${{{plugin_var}[_PLUGIN_DIR]}}/modules\" ]]; then"
+        );
+        let _ = writeln!(
+            block,
+            "        {plugin_var}[_PLUGIN_MODS_DIR]=\"${{{plugin_var}[_PLUGIN_DIR]}}/modules\"\n"
+        );
+        ("        ", format!("${{{plugin_var}[_PLUGIN_MODS_DIR]}}"))
+    };
+    let remember_fn = if uses_zplugins {
+        format!("@zplugins_remember_fn {plugin_name}")
+    } else {
+        format!(".{plugin_name}_remember_fn")
+    };
+    let _ = writeln!(block, "{indent}local module fn");
+    let _ = writeln!(block, "{indent}local -a fns_before fns_after");
+    let _ = writeln!(block, "{indent}for module in {modules_dir}/*.zsh(N.on); do");
+    let _ = writeln!(block, "{indent}    fns_before=( ${{(k)functions}} )");
+    let _ = writeln!(block, "{indent}    source \"${{module}}\"");
+    let _ = writeln!(block, "{indent}    fns_after=( ${{(k)functions}} )");
+    let _ = writeln!(
+        block,
+        "{indent}    for fn in ${{fns_after:|fns_before}}; do"
+    );
+    let _ = writeln!(block, "{indent}        {remember_fn} ${{fn}}");
+    let _ = writeln!(block, "{indent}    done");
+    let _ = writeln!(block, "{indent}done");
+    if !uses_zplugins {
+        let _ = writeln!(block, "    fi");
+    }
+    block
+}
+
+/// Render the `env-save` block of the plugin's `_plugin_init` function.
+///
+/// Each variable's previous state, unset, set, or set and exported, is saved before
+/// it is exported with its default value, if it has one and is not already set.
+pub(crate) fn env_save_block(
+    plugin_name: &str,
+    plugin_var: &str,
+    uses_zplugins: bool,
+    vars: &[EnvVar],
+) -> String {
+    let mut block = String::new();
+    for var in vars {
+        let name = var.name();
+        let _ = writeln!(block, "    # {name}, restored when the plugin is unloaded.");
+        if uses_zplugins {
+            let _ = writeln!(block, "    @zplugins_envvar_save {plugin_name} {name}");
+        } else {
+            let state = format!("{plugin_var}[_OLD_{name}_STATE]");
+            let _ = writeln!(block, "    if (( ! ${{+{name}}} )); then");
+            let _ = writeln!(block, "        {state}=unset");
+            let _ = writeln!(block, "    else");
+            let _ = writeln!(block, "        {plugin_var}[_OLD_{name}]=\"${{{name}}}\"");
+            let _ = writeln!(block, "        if [[ ${{(t){name}}} == *export* ]]; then");
+            let _ = writeln!(block, "            {state}=exported");
+            let _ = writeln!(block, "        else");
+            let _ = writeln!(block, "            {state}=set");
+            let _ = writeln!(block, "        fi");
+            let _ = writeln!(block, "    fi");
+        }
+        match var.default() {
+            Some(default) => {
+                let _ = writeln!(
+                    block,
+                    "    export {name}=${{{name}-{}}}",
+                    shell_quote(default)
+                );
+            }
+            None => {
+                let _ = writeln!(block, "    export {name}");
+            }
+        }
+    }
+    block
+}
+
+/// Render the `env-restore` block of the plugin's `_plugin_unload` function.
+///
+/// Each variable is returned to the state saved by [`env_save_block`]; unset,
+/// or its previous value either exported or not.
+pub(crate) fn env_restore_block(
+    plugin_name: &str,
+    plugin_var: &str,
+    uses_zplugins: bool,
+    vars: &[EnvVar],
+) -> String {
+    let mut block = String::new();
+    for var in vars {
+        let name = var.name();
+        if uses_zplugins {
+            let _ = writeln!(block, "    @zplugins_envvar_restore {plugin_name} {name}");
+        } else {
+            let old = format!("\"${{{plugin_var}[_OLD_{name}]}}\"");
+            let _ = writeln!(
+                block,
+                "    case \"${{{plugin_var}[_OLD_{name}_STATE]}}\" in"
+            );
+            let _ = writeln!(block, "        (unset) unset {name} ;;");
+            let _ = writeln!(block, "        (exported) export {name}={old} ;;");
+            let _ = writeln!(block, "        (set) typeset -g +x {name}={old} ;;");
+            let _ = writeln!(block, "    esac");
+        }
+    }
+    block
+}
+
+/// Render the `hook-functions` block of the plugin file, at the top level of the file.
+///
+/// Each hook function is an empty stub for the plugin author to complete, and is
+/// remembered so that it is removed when the plugin is unloaded.
+pub(crate) fn hook_functions_block(
+    plugin_name: &str,
+    uses_zplugins: bool,
+    hooks: &[ShellHook],
+) -> String {
+    let mut block = String::new();
+    for hook in hooks {
+        let function = hook.function_name(plugin_name);
+        let _ = writeln!(block, "#");
+        let _ = writeln!(
+            block,
+            "# @description Called by the `{hook}` hook, while the plugin is loaded."
+        );
+        let _ = writeln!(block, "#");
+        let _ = writeln!(block, "{function}() {{");
+        let _ = writeln!(block, "    builtin emulate -L zsh");
+        let _ = writeln!(block);
+        let _ = writeln!(block, "}}");
+        if uses_zplugins {
+            let _ = writeln!(block, "@zplugins_remember_fn {plugin_name} {function}");
+        } else {
+            let _ = writeln!(block, ".{plugin_name}_remember_fn {function}");
+        }
+        let _ = writeln!(block);
+    }
+    block
+}
+
+/// Render the `hooks-add` block of the plugin's `_plugin_init` function.
+///
+/// For plain plugins each registered hook is also recorded, as `HOOK:FUNCTION`,
+/// in the `_HOOKS` key of the plugin hash.
+pub(crate) fn hooks_add_block(
+    plugin_name: &str,
+    plugin_var: &str,
+    uses_zplugins: bool,
+    hooks: &[ShellHook],
+) -> String {
+    let mut block = String::new();
+    for hook in hooks {
+        let function = hook.function_name(plugin_name);
+        let _ = writeln!(block, "    add-zsh-hook {hook} {function}");
+        if !uses_zplugins {
+            let _ = writeln!(
+                block,
+                "    {plugin_var}[_HOOKS]+=\"${{{plugin_var}[_HOOKS]:+,}}{hook}:{function}\""
+            );
+        }
+    }
+    block
+}
+
+/// Render the `hooks-remove` block of the plugin's `_plugin_unload` function.
+pub(crate) fn hooks_remove_block(plugin_name: &str, hooks: &[ShellHook]) -> String {
+    let mut block = String::new();
+    for hook in hooks {
+        let _ = writeln!(
+            block,
+            "    add-zsh-hook -d {hook} {}",
+            hook.function_name(plugin_name)
+        );
+    }
+    block
+}
+
+/// Render the `widget-functions` block of the plugin file, at the top level of the file.
+pub(crate) fn widget_functions_block(
+    plugin_name: &str,
+    uses_zplugins: bool,
+    widget: &Widget,
+) -> String {
+    let mut block = String::new();
+    let function = widget.function_name(plugin_name);
+    let _ = writeln!(block, "#");
+    let _ = writeln!(
+        block,
+        "# @description The `{}` line editor widget.",
+        widget.name()
+    );
+    let _ = writeln!(block, "#");
+    let _ = writeln!(block, "{function}() {{");
+    let _ = writeln!(block, "    builtin emulate -L zsh");
+    let _ = writeln!(block);
+    let _ = writeln!(block, "}}");
+    if uses_zplugins {
+        let _ = writeln!(block, "@zplugins_remember_fn {plugin_name} {function}");
+    } else {
+        let _ = writeln!(block, ".{plugin_name}_remember_fn {function}");
+    }
+    let _ = writeln!(block);
+    block
+}
+
+/// Render the `widgets-add` block of the plugin's `_plugin_init` function.
+///
+/// If the widget has a key binding the widget previously bound to the key sequence
+/// is saved, for plain plugins in the plugin hash and otherwise in a global variable.
+pub(crate) fn widgets_add_block(
+    plugin_name: &str,
+    plugin_var: &str,
+    uses_zplugins: bool,
+    widget: &Widget,
+) -> String {
+    let mut block = String::new();
+    let name = widget.name();
+    let _ = writeln!(
+        block,
+        "    zle -N {name} {}",
+        widget.function_name(plugin_name)
+    );
+    if !uses_zplugins {
+        let _ = writeln!(
+            block,
+            "    {plugin_var}[_WIDGETS]+=\"${{{plugin_var}[_WIDGETS]:+,}}{name}\""
+        );
+    }
+    if let Some(binding) = widget.binding() {
+        let (keymap, keys) = (binding.keymap(), shell_quote(binding.key_sequence()));
+        let previous = format!("\"${{${{(z)\"$(bindkey -M {keymap} {keys})\"}}[2]}}\"");
+        if uses_zplugins {
+            let _ = writeln!(
+                block,
+                "    typeset -g {}={previous}",
+                old_binding_var(plugin_var, widget)
+            );
+        } else {
+            let _ = writeln!(block, "    {plugin_var}[_OLD_BINDING_{name}]={previous}");
+        }
+        let _ = writeln!(block, "    bindkey -M {keymap} {keys} {name}");
+    }
+    block
+}
+
+/// Render the `widgets-remove` block of the plugin's `_plugin_unload` function.
+///
+/// The key binding saved by [`widgets_add_block`] is restored before the widget
+/// is deleted, a key sequence that was not bound is bound to `undefined-key`.
+/// Both only happen if init defined the widget, as unload also runs when init
+/// stops early, e.g. on a missing command.
+pub(crate) fn widgets_remove_block(
+    plugin_var: &str,
+    uses_zplugins: bool,
+    widget: &Widget,
+) -> String {
+    let mut block = String::new();
+    let name = widget.name();
+    let previous = if uses_zplugins {
+        old_binding_var(plugin_var, widget)
+    } else {
+        format!("{plugin_var}[_OLD_BINDING_{name}]")
+    };
+    let defined = match (uses_zplugins, widget.binding()) {
+        (false, _) => format!("[[ \",${{{plugin_var}[_WIDGETS]}},\" == *\",{name},\"* ]]"),
+        (true, Some(_)) => format!("(( ${{+{previous}}} ))"),
+        (true, None) => format!("(( ${{+widgets[{name}]}} ))"),
+    };
+    let _ = writeln!(block, "    if {defined}; then");
+    if let Some(binding) = widget.binding() {
+        let (keymap, keys) = (binding.keymap(), shell_quote(binding.key_sequence()));
+        let _ = writeln!(
+            block,
+            "        bindkey -M {keymap} {keys} \"${{{previous}:-undefined-key}}\""
+        );
+        if uses_zplugins {
+            let _ = writeln!(block, "        unset {previous}");
+        }
+    }
+    let _ = writeln!(block, "        zle -D {name}");
+    let _ = writeln!(block, "    fi");
+    block
+}
+
+/// Render the `setting-functions` block of the plugin file, at the top level of the file.
+///
+/// The helper prints the value of the setting, or the default if it is not set with
+/// `zstyle`, except for a boolean setting which is returned as the exit status.
+pub(crate) fn setting_function_block(
+    plugin_name: &str,
+    uses_zplugins: bool,
+    setting: &Setting,
+) -> String {
+    let mut block = String::new();
+    let (name, function) = (setting.name(), setting.function_name(plugin_name));
+    let context = format!("':plugin:{plugin_name}'");
+    let _ = writeln!(block, "#");
+    let _ = writeln!(
+        block,
+        "# @description Look up the `{name}` setting, {}.",
+        setting.summary()
+    );
+    let _ = writeln!(block, "#");
+    let _ = writeln!(block, "# @example");
+    let _ = writeln!(block, "#   zstyle {context} {name} VALUE");
+    let _ = writeln!(block, "#");
+    if setting.kind() == SettingType::Bool {
+        let _ = writeln!(block, "# @exitcode 0 If the setting is true.");
+        let _ = writeln!(block, "# @exitcode 1 If the setting is false.");
+    } else {
+        let _ = writeln!(block, "# @stdout The value of the setting.");
+    }
+    let _ = writeln!(block, "#");
+    let _ = writeln!(block, "{function}() {{");
+    let _ = writeln!(block, "    builtin emulate -L zsh");
+    let _ = writeln!(block);
+    let default = shell_quote(setting.default());
+    match setting.kind() {
+        SettingType::Bool if setting.default_bool() => {
+            let _ = writeln!(block, "    zstyle -T {context} {name}");
+        }
+        SettingType::Bool => {
+            let _ = writeln!(block, "    zstyle -t {context} {name}");
+        }
+        SettingType::String => {
+            let _ = writeln!(block, "    local value");
+            let _ = writeln!(
+                block,
+                "    zstyle -s {context} {name} value || value={default}"
+            );
+            let _ = writeln!(block, "    print -r -- \"${{value}}\"");
+        }
+        SettingType::Int => {
+            let _ = writeln!(block, "    local value");
+            let _ = writeln!(
+                block,
+                "    if ! zstyle -s {context} {name} value || [[ ${{value}} != (|-)<-> ]]; then"
+            );
+            let _ = writeln!(block, "        value={default}");
+            let _ = writeln!(block, "    fi");
+            let _ = writeln!(block, "    print -r -- \"${{value}}\"");
+        }
+        SettingType::Array => {
+            let defaults = setting
+                .default_array()
+                .into_iter()
+                .map(shell_quote)
+                .collect::<Vec<_>>()
+                .join(" ");
+            let _ = writeln!(block, "    local -a value");
+            let _ = writeln!(
+                block,
+                "    zstyle -a {context} {name} value || value=( {defaults} )"
+            );
+            let _ = writeln!(block, "    print -r -- \"${{value[@]}}\"");
+        }
+    }
+    let _ = writeln!(block, "}}");
+    if uses_zplugins {
+        let _ = writeln!(block, "@zplugins_remember_fn {plugin_name} {function}");
+    } else {
+        let _ = writeln!(block, ".{plugin_name}_remember_fn {function}");
+    }
+    let _ = writeln!(block);
+    block
+}
+
+/// The helper functions generated for hooks, widgets and settings, those defined in
+/// the `hook-functions`, `widget-functions` and `setting-functions` blocks of the
+/// plugin file `content`, rather than part of the plugin's public interface.
+pub(crate) fn generated_helpers(content: &str) -> Vec<String> {
+    [B_HOOK_FUNCTIONS, B_WIDGET_FUNCTIONS, B_SETTING_FUNCTIONS]
+        .into_iter()
+        .filter_map(|name| block_content(content, name))
+        .flat_map(str::lines)
+        .filter_map(|line| line.trim_end().strip_suffix("() {"))
+        .filter(|function| !function.is_empty() && !function.starts_with(char::is_whitespace))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Render the block `install` adds to `.zshrc`, loading the plugin installed in
+/// `plugin_dir` directly or with `manager`.
+pub(crate) fn install_block(
+    manager: PluginManager,
+    plugin_dir: &Path,
+    plugin_file: &str,
+) -> String {
+    let dir = shell_quote(&plugin_dir.to_string_lossy());
+    let mut block = String::new();
+    let _ = match manager {
+        PluginManager::None => writeln!(
+            block,
+            "source {}",
+            shell_quote(&plugin_dir.join(plugin_file).to_string_lossy())
+        ),
+        PluginManager::Zplug => writeln!(block, "zplug {dir}, from:local"),
+        PluginManager::Antigen => writeln!(block, "antigen bundle {dir} --no-local-clone"),
+    };
+    block
+}
+
+/// The current content of the block `name`, if its markers are present.
+pub(crate) fn block_content<'a>(content: &'a str, name: &str) -> Option<&'a str> {
+    let (start, finish) = block_range(content, name)?;
+    Some(&content[start..finish])
+}
+
+/// Append `addition` to the end of the block `name` in the file at `path`.
+///
+/// # Errors
+///
+/// Returns [`Error::MarkerNotFound`] if the file does not contain the block's markers.
+pub(crate) fn append_to_block(path: &Path, name: &str, addition: &str) -> Result<(), Error> {
+    trace!("append_to_block => path: {path:?}, name: {name}");
+    let current = read_to_string(path)?;
+    let existing = block_content(&current, name).ok_or_else(|| marker_not_found(path, name))?;
+    let replacement = format!("{existing}{addition}");
+    update_block(path, name, &replacement)
+}
+
+/// Replace the content of the block `name` in the file at `path`.
+///
+/// # Errors
+///
+/// Returns [`Error::MarkerNotFound`] if the file does not contain the block's markers.
+pub(crate) fn update_block(path: &Path, name: &str, replacement: &str) -> Result<(), Error> {
+    trace!("update_block => path: {path:?}, name: {name}");
+    let current = read_to_string(path)?;
+    let updated =
+        replace_block(&current, name, replacement).ok_or_else(|| marker_not_found(path, name))?;
+    if updated != current {
+        write(path, updated)?;
+    }
+    Ok(())
+}
+
+/// Replace the lines between the begin and end markers `name`, returning `None`
+/// if either marker is missing. The end marker keeps its indentation.
+pub(crate) fn replace_block(content: &str, name: &str, replacement: &str) -> Option<String> {
+    let (start, finish) = block_range(content, name)?;
+    Some(format!(
+        "{}{replacement}{}",
+        &content[..start],
+        &content[finish..]
+    ))
+}
+
+/// Add the block `name`, containing `block`, to the end of `content`, separated
+/// from any existing content by a blank line.
+pub(crate) fn add_block(content: &str, name: &str, block: &str) -> String {
+    let mut updated = content.to_string();
+    if !updated.is_empty() {
+        if !updated.ends_with('\n') {
+            updated.push('\n');
+        }
+        updated.push('\n');
+    }
+    let _ = writeln!(updated, "{}", begin_marker(name));
+    updated.push_str(block);
+    let _ = writeln!(updated, "{}", end_marker(name));
+    updated
+}
+
+/// Remove the block `name`, with its markers and any blank line before it, from
+/// `content`, returning `None` if either marker is missing.
+pub(crate) fn remove_block(content: &str, name: &str) -> Option<String> {
+    let (begin, _) = marker_line(content, &begin_marker(name), 0)?;
+    let (_, finish) = block_range(content, name)?;
+    let head = &content[..begin];
+    let head = head
+        .strip_suffix("\n\n")
+        .map_or(head, |h| &head[..h.len() + 1]);
+    let tail = content[finish..]
+        .find('\n')
+        .map_or("", |i| &content[finish + i + 1..]);
+    Some(format!("{head}{tail}"))
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+/// The byte range of the lines between the begin and end markers `name`.
+fn block_range(content: &str, name: &str) -> Option<(usize, usize)> {
+    let (_, begin_end) = marker_line(content, &begin_marker(name), 0)?;
+    let start = begin_end + content[begin_end..].find('\n')? + 1;
+    let (finish, _) = marker_line(content, &end_marker(name), start)?;
+    Some((start, finish))
+}
+
+/// The start and end of the first line, at or after `from`, that holds only
+/// `marker`, ignoring indentation; a marker that is the prefix of another block's
+/// marker, such as `git` of `git_tools`, does not match it.
+fn marker_line(content: &str, marker: &str, from: usize) -> Option<(usize, usize)> {
+    let mut line_start = from;
+    while line_start < content.len() {
+        let line_end = content[line_start..]
+            .find('\n')
+            .map_or(content.len(), |i| line_start + i);
+        if content[line_start..line_end].trim() == marker {
+            return Some((line_start, line_end));
+        }
+        line_start = line_end + 1;
+    }
+    None
+}
+
+/// The global variable a zplugins plugin saves a widget's previous key binding in.
+fn old_binding_var(plugin_var: &str, widget: &Widget) -> String {
+    format!(
+        "{plugin_var}_OLD_BINDING_{}",
+        widget.name().replace(['-', '.'], "_").to_ascii_uppercase()
+    )
+}
+
+/// Quote `value` as a single Zsh word, with no expansion.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn begin_marker(name: &str) -> String {
+    format!("# zsh-plugin:begin:{name}")
+}
+
+fn end_marker(name: &str) -> String {
+    format!("# zsh-plugin:end:{name}")
+}
+
+fn marker_not_found(path: &Path, name: &str) -> Error {
+    error!("Source {path:?} has no '{name}' markers");
+    Error::MarkerNotFound {
+        path: PathBuf::from(path),
+        marker: begin_marker(name),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::Keymap;
+    use clap::ValueEnum;
+
+    #[test]
+    fn replace_indented_block() {
+        let content = "f() {\n    # zsh-plugin:begin:x\n    old\n    # zsh-plugin:end:x\n}\n";
+        assert_eq!(
+            replace_block(content, "x", "    new\n    lines\n").unwrap(),
+            "f() {\n    # zsh-plugin:begin:x\n    new\n    lines\n    # zsh-plugin:end:x\n}\n"
+        );
+    }
+
+    #[test]
+    fn replace_empty_block() {
+        let content = "# zsh-plugin:begin:x\n# zsh-plugin:end:x\n";
+        let updated = replace_block(content, "x", "a\n").unwrap();
+        assert_eq!(updated, "# zsh-plugin:begin:x\na\n# zsh-plugin:end:x\n");
+        assert_eq!(replace_block(&updated, "x", "").unwrap(), content);
+    }
+
+    #[test]
+    fn render_dependencies() {
+        let deps = vec!["shlog".to_string(), "my-base".to_string()];
+        assert_eq!(
+            dependencies_block("demo", "demo", true, &deps),
+            "@zplugins_declare_plugin_dependencies demo shlog my-base\n"
+        );
+        assert_eq!(dependencies_block("demo", "demo", true, &[]), "");
+        assert!(
+            dependencies_block("demo", "demo", false, &deps)
+                .starts_with("if (( ! ${+SHLOG} )); then\n")
+        );
+        assert!(dependencies_block("demo", "demo", false, &deps).contains("${+MY_BASE}"));
+    }
+
+    #[test]
+    fn render_modules() {
+        let block = modules_block("demo", "DEMO", true);
+        assert!(block.starts_with("    local module fn\n"));
+        assert!(
+            block.contains("    for module in ${DEMO_PLUGIN_PATH:h}/modules/*.zsh(N.on); do\n")
+        );
+        assert!(block.contains("            @zplugins_remember_fn demo ${fn}\n"));
+        let block = modules_block("demo", "DEMO", false);
+        assert!(block.starts_with("    if [[ -d \"${DEMO[_PLUGIN_DIR]}/modules\" ]]; then\n"));
+        assert!(
+            block.contains("        for module in ${DEMO[_PLUGIN_MODS_DIR]}/*.zsh(N.on); do\n")
+        );
+        assert!(block.contains("                .demo_remember_fn ${fn}\n"));
+        assert!(block.ends_with("        done\n    fi\n"));
+    }
+
+    #[test]
+    fn render_requirements() {
+        let git: CommandRequirement = "git>=2.30".parse().unwrap();
+        let jq = CommandRequirement::new("jq", None).with_on_missing(OnMissing::Skip);
+        assert_eq!(
+            requirements_block("demo", &[git, jq]),
+            r#"    autoload -Uz is-at-least
+    if (( ! $+commands[git] )); then
+        print -u2 "demo: warning, the command 'git' was not found"
+    elif [[ "$(command git --version 2>&1)" =~ '[0-9]+(\.[0-9]+)+' ]] \
+        && ! is-at-least 2.30 "${MATCH}"; then
+        print -u2 "demo: warning, the command 'git' is version ${MATCH}, 2.30 or later is required"
+    fi
+    if (( ! $+commands[jq] )); then
+        return 1
+    fi
+"#
+        );
+    }
+
+    #[test]
+    fn render_env_vars() {
+        let vars: Vec<EnvVar> = vec!["EDITOR=it's".parse().unwrap(), "PAGER".parse().unwrap()];
+        let save = env_save_block("demo", "DEMO", false, &vars);
+        assert!(save.contains("    export EDITOR=${EDITOR-'it'\\''s'}\n"));
+        assert!(save.contains("    export PAGER\n"));
+        assert!(save.contains("DEMO[_OLD_PAGER_STATE]=unset"));
+        let restore = env_restore_block("demo", "DEMO", false, &vars);
+        assert!(
+            restore.contains("        (set) typeset -g +x EDITOR=\"${DEMO[_OLD_EDITOR]}\" ;;\n")
+        );
+        assert_eq!(
+            env_restore_block("demo", "DEMO", true, &vars),
+            "    @zplugins_envvar_restore demo EDITOR\n    @zplugins_envvar_restore demo PAGER\n"
+        );
+    }
+
+    #[test]
+    fn render_hooks() {
+        let hooks = [ShellHook::Precmd, ShellHook::Chpwd];
+        assert_eq!(
+            hooks_add_block("demo", "DEMO", false, &hooks[..1]),
+            "    add-zsh-hook precmd demo_precmd_hook\n    DEMO[_HOOKS]+=\"${DEMO[_HOOKS]:+,}precmd:demo_precmd_hook\"\n"
+        );
+        assert_eq!(
+            hooks_remove_block("demo", &hooks),
+            "    add-zsh-hook -d precmd demo_precmd_hook\n    add-zsh-hook -d chpwd demo_chpwd_hook\n"
+        );
+        let functions = hook_functions_block("demo", true, &hooks[1..]);
+        assert!(functions.contains("\ndemo_chpwd_hook() {\n"));
+        assert!(functions.contains("\n@zplugins_remember_fn demo demo_chpwd_hook\n"));
+    }
+
+    #[test]
+    fn render_widgets() {
+        let widget: Widget = "fzf-history".parse().unwrap();
+        let widget = widget.with_binding("^R", Keymap::Viins);
+        assert_eq!(
+            widgets_add_block("demo", "DEMO", false, &widget),
+            r#"    zle -N fzf-history demo_fzf_history_widget
+    DEMO[_WIDGETS]+="${DEMO[_WIDGETS]:+,}fzf-history"
+    DEMO[_OLD_BINDING_fzf-history]="${${(z)"$(bindkey -M viins '^R')"}[2]}"
+    bindkey -M viins '^R' fzf-history
+"#
+        );
+        assert_eq!(
+            widgets_remove_block("DEMO", true, &widget),
+            r#"    if (( ${+DEMO_OLD_BINDING_FZF_HISTORY} )); then
+        bindkey -M viins '^R' "${DEMO_OLD_BINDING_FZF_HISTORY:-undefined-key}"
+        unset DEMO_OLD_BINDING_FZF_HISTORY
+        zle -D fzf-history
+    fi
+"#
+        );
+    }
+
+    #[test]
+    fn render_widgets_remove_after_aborted_init() {
+        // Unload also runs when init returns before `widgets-add`, so the user's own
+        // binding, and an undefined widget, must be left alone.
+        let widget: Widget = "fzf-history".parse().unwrap();
+        let bound = widget.clone().with_binding("^R", Keymap::Emacs);
+        assert_eq!(
+            widgets_remove_block("DEMO", false, &bound),
+            r#"    if [[ ",${DEMO[_WIDGETS]}," == *",fzf-history,"* ]]; then
+        bindkey -M emacs '^R' "${DEMO[_OLD_BINDING_fzf-history]:-undefined-key}"
+        zle -D fzf-history
+    fi
+"#
+        );
+        assert_eq!(
+            widgets_remove_block("DEMO", true, &widget),
+            "    if (( ${+widgets[fzf-history]} )); then\n        zle -D fzf-history\n    fi\n"
+        );
+    }
+
+    #[test]
+    fn render_settings() {
+        let name = "depth".parse().unwrap();
+        let setting = Setting::new(&name, SettingType::Int, Some("3"), None).unwrap();
+        let block = setting_function_block("demo", false, &setting);
+        assert!(block.contains("\ndemo_setting_depth() {\n"));
+        assert!(block.contains(
+            "    if ! zstyle -s ':plugin:demo' depth value || [[ ${value} != (|-)<-> ]]; then\n        value='3'\n"
+        ));
+        let name = "enabled".parse().unwrap();
+        let setting = Setting::new(&name, SettingType::Bool, Some("yes"), None).unwrap();
+        assert!(
+            setting_function_block("demo", true, &setting)
+                .contains("    zstyle -T ':plugin:demo' enabled\n}\n@zplugins_remember_fn")
+        );
+    }
+
+    #[test]
+    fn find_generated_helpers() {
+        let hook = ShellHook::value_variants()[0];
+        let widget: Widget = "fzf-history".parse().unwrap();
+        let setting =
+            Setting::new(&"depth".parse().unwrap(), SettingType::Int, None, None).unwrap();
+        let content = format!(
+            "demo_widget() {{\n}}\n\n\
+             # zsh-plugin:begin:hook-functions\n{}# zsh-plugin:end:hook-functions\n\n\
+             # zsh-plugin:begin:widget-functions\n{}# zsh-plugin:end:widget-functions\n\n\
+             demo_setting_color() {{\n}}\n\n\
+             # zsh-plugin:begin:setting-functions\n{}# zsh-plugin:end:setting-functions\n",
+            hook_functions_block("demo", false, &[hook]),
+            widget_functions_block("demo", false, &widget),
+            setting_function_block("demo", false, &setting),
+        );
+        assert_eq!(
+            generated_helpers(&content),
+            vec![
+                hook.function_name("demo"),
+                widget.function_name("demo"),
+                setting.function_name("demo"),
+            ]
+        );
+    }
+
+    #[test]
+    fn blocks_with_prefixed_names() {
+        let content = "a\n\n# zsh-plugin:begin:git_tools\ntools\n# zsh-plugin:end:git_tools\n\n# zsh-plugin:begin:git\ngit\n# zsh-plugin:end:git\n";
+        assert_eq!(block_content(content, "git"), Some("git\n"));
+        assert_eq!(block_content(content, "git_tools"), Some("tools\n"));
+        assert_eq!(
+            replace_block(content, "git", "new\n").unwrap(),
+            content.replace("\ngit\n", "\nnew\n")
+        );
+        assert_eq!(
+            remove_block(content, "git").unwrap(),
+            "a\n\n# zsh-plugin:begin:git_tools\ntools\n# zsh-plugin:end:git_tools\n"
+        );
+
+        let content = "# zsh-plugin:begin:git_tools\ntools\n# zsh-plugin:end:git_tools\n";
+        assert_eq!(block_content(content, "git"), None);
+        assert_eq!(remove_block(content, "git"), None);
+    }
+
+    #[test]
+    fn block_content_between_markers() {
+        let content = "# zsh-plugin:begin:x\na\n# zsh-plugin:end:x\n";
+        assert_eq!(block_content(content, "x"), Some("a\n"));
+        assert_eq!(block_content("", "x"), None);
+    }
+
+    #[test]
+    fn replace_missing_block() {
+        assert_eq!(replace_block("# zsh-plugin:begin:x\n", "x", ""), None);
+        assert_eq!(replace_block("# zsh-plugin:end:x\n", "x", ""), None);
+    }
+
+    #[test]
+    fn add_and_remove_block() {
+        let content = "export EDITOR=vi";
+        let added = add_block(content, "demo", "source '/p/demo.plugin.zsh'\n");
+        assert_eq!(
+            added,
+            "export EDITOR=vi\n\n# zsh-plugin:begin:demo\nsource '/p/demo.plugin.zsh'\n# zsh-plugin:end:demo\n"
+        );
+        assert_eq!(
+            remove_block(&added, "demo").as_deref(),
+            Some("export EDITOR=vi\n")
+        );
+        assert_eq!(remove_block(content, "demo"), None);
+    }
+}
@@ -0,0 +1,383 @@
+//! Checks of an existing plugin's sources and generated content.
+//!
+//! The `check` command runs the following checks over all of the plugin's files,
+//! the generated Git pre-commit hook runs the first two over only the staged files
+//! before running this command.
+//!
+//! 1. **Syntax**; each Zsh source is parsed with `zsh -n`.
+//! 2. **Shellcheck**; all sources are linted with `shellcheck`, as in the `Makefile`.
+//! 3. **README**; the generated sections of `README.md` are up to date.
+//! 4. **Manifest**; the `plugin.toml` manifest is up to date.
+//! 5. **Bash**; only with `--bash`, the autoload functions and modules the Bash
+//!    wrapper sources are searched for Zsh-only syntax, such as `typeset -g`,
+//!    parameter modifiers and flags like `${0:h}` and `${(%)...}`, glob qualifiers,
+//!    and Zsh builtins like `autoload`. Public functions defined in the plugin file,
+//!    and aliases, are reported as not available from Bash.
+//!
+//! Checks whose tool is not installed are reported as skipped rather than failed.
+
+use crate::{
+    doc::PluginDocs,
+    error::Error,
+    manifest::{P_MANIFEST, manifest_is_current},
+    plugin::Plugin,
+    readme::readme_is_current,
+    templates::{P_MODULES_DIR, P_README},
+};
+use regex::Regex;
+use serde::Serialize;
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs::read_to_string,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process::{Command, ExitCode},
+    sync::LazyLock,
+};
+use tracing::trace;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// The outcome of a single check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    Passed,
+    /// The check failed, with any output from the tool.
+    Failed(String),
+    /// The check could not be run, with the reason.
+    Skipped(String),
+}
+
+/// The combined outcome of all checks against a plugin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum CheckStatus {
+    /// No check failed, and at least one passed.
+    Passed,
+    /// At least one check failed.
+    Failed,
+    /// Every check was skipped.
+    Skipped,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Run all checks against `plugin`, including the Bash check if `bash` is set,
+/// reporting each outcome, and fail if any check failed.
+pub(crate) fn check_plugin(plugin: &Plugin, bash: bool) -> Result<ExitCode, Error> {
+    trace!("check_plugin => plugin: {plugin:?}, bash: {bash}");
+    let results = run_checks(plugin, bash)?;
+
+    println!("Checking plugin '{}'", plugin.display_name());
+    let count = results.len();
+    for (i, (name, outcome)) in results.iter().enumerate() {
+        let branch = if i + 1 == count { "└─" } else { "├─" };
+        match outcome {
+            Outcome::Passed => println!("{branch} {name}: passed"),
+            Outcome::Failed(_) => println!("{branch} {name}: failed"),
+            Outcome::Skipped(reason) => println!("{branch} {name}: skipped, {reason}"),
+        }
+    }
+
+    let mut failed = false;
+    for (name, outcome) in &results {
+        if let Outcome::Failed(output) = outcome {
+            failed = true;
+            if !output.trim().is_empty() {
+                eprintln!("\n{name}:\n{}", output.trim_end());
+            }
+        }
+    }
+
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Run all checks against `plugin`, without reporting, and combine their outcomes.
+pub(crate) fn check_status(plugin: &Plugin) -> Result<CheckStatus, Error> {
+    let results = run_checks(plugin, false)?;
+    Ok(
+        if results
+            .iter()
+            .any(|(_, outcome)| matches!(outcome, Outcome::Failed(_)))
+        {
+            CheckStatus::Failed
+        } else if results
+            .iter()
+            .any(|(_, outcome)| outcome == &Outcome::Passed)
+        {
+            CheckStatus::Passed
+        } else {
+            CheckStatus::Skipped
+        },
+    )
+}
+
+/// The line number, and description, of each Zsh-only construct in `content`,
+/// ignoring comment lines.
+pub(crate) fn zsh_only_syntax(content: &str) -> Vec<(usize, &'static str)> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim_start().starts_with('#'))
+        .flat_map(|(i, line)| {
+            ZSH_ONLY_SYNTAX
+                .iter()
+                .filter(|(_, pattern)| pattern.is_match(line))
+                .map(move |(construct, _)| (i + 1, *construct))
+        })
+        .collect()
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Run all checks against `plugin`, returning the name and outcome of each.
+fn run_checks(plugin: &Plugin, bash: bool) -> Result<Vec<(&'static str, Outcome)>, Error> {
+    let zsh_sources = zsh_sources(plugin)?;
+    let mut all_sources = zsh_sources.clone();
+    let wrapper = PathBuf::from(format!("{}.bash", plugin.name()));
+    if plugin.root().join(&wrapper).is_file() {
+        all_sources.push(wrapper);
+    }
+
+    let mut results = vec![
+        ("Syntax", check_syntax(plugin.root(), &zsh_sources)),
+        ("Shellcheck", check_lint(plugin.root(), &all_sources)),
+        ("README", check_readme(plugin)?),
+        ("Manifest", check_manifest(plugin)?),
+    ];
+    if bash {
+        results.push(("Bash", check_bash(plugin)?));
+    }
+    Ok(results)
+}
+
+/// The plugin file, modules and autoload functions, relative to the plugin root.
+fn zsh_sources(plugin: &Plugin) -> Result<Vec<PathBuf>, Error> {
+    let mut sources = vec![plugin.plugin_file()];
+    sources.extend(plugin.module_files()?);
+    sources.extend(plugin.function_files()?);
+    Ok(sources
+        .into_iter()
+        .map(|path| {
+            path.strip_prefix(plugin.root())
+                .map(Path::to_path_buf)
+                .unwrap_or(path)
+        })
+        .collect())
+}
+
+fn check_syntax(root: &Path, sources: &[PathBuf]) -> Outcome {
+    let mut output = String::new();
+    for source in sources {
+        match run_tool(root, "zsh", &["-n".as_ref(), source.as_os_str()]) {
+            Outcome::Failed(text) => output.push_str(&text),
+            Outcome::Skipped(reason) => return Outcome::Skipped(reason),
+            Outcome::Passed => {}
+        }
+    }
+    if output.is_empty() {
+        Outcome::Passed
+    } else {
+        Outcome::Failed(output)
+    }
+}
+
+fn check_lint(root: &Path, sources: &[PathBuf]) -> Outcome {
+    let mut args = vec![
+        "--check-sourced".as_ref(),
+        "--color=never".as_ref(),
+        "--shell=bash".as_ref(),
+    ];
+    args.extend(sources.iter().map(|source| source.as_os_str()));
+    run_tool(root, "shellcheck", &args)
+}
+
+fn check_readme(plugin: &Plugin) -> Result<Outcome, Error> {
+    if !plugin.root().join(P_README).is_file() {
+        return Ok(Outcome::Skipped(format!("no '{P_README}' file")));
+    }
+    Ok(match readme_is_current(plugin) {
+        Ok(true) => Outcome::Passed,
+        Ok(false) => Outcome::Failed(
+            "Run 'zsh-plugin readme sync' to update the generated sections.".to_string(),
+        ),
+        Err(Error::MarkerNotFound { marker, .. }) => {
+            Outcome::Skipped(format!("no '{marker}' marker"))
+        }
+        Err(e) => return Err(e),
+    })
+}
+
+fn check_manifest(plugin: &Plugin) -> Result<Outcome, Error> {
+    if !plugin.root().join(P_MANIFEST).is_file() {
+        return Ok(Outcome::Skipped(format!("no '{P_MANIFEST}' file")));
+    }
+    Ok(if manifest_is_current(plugin)? {
+        Outcome::Passed
+    } else {
+        Outcome::Failed("Run 'zsh-plugin manifest sync' to update the manifest.".to_string())
+    })
+}
+
+/// Search the files the Bash wrapper sources, the autoload functions and modules,
+/// for Zsh-only syntax, and report what the plugin file alone provides, as it is
+/// only sourced by Zsh.
+fn check_bash(plugin: &Plugin) -> Result<Outcome, Error> {
+    let wrapper = format!("{}.bash", plugin.name());
+    if !plugin.root().join(&wrapper).is_file() {
+        return Ok(Outcome::Skipped(format!("no '{wrapper}' file")));
+    }
+    let mut output = String::new();
+    for file in plugin
+        .function_files()?
+        .into_iter()
+        .chain(plugin.module_files()?)
+    {
+        let name = file.strip_prefix(plugin.root()).unwrap_or(&file).display();
+        for (line, construct) in zsh_only_syntax(&read_to_string(&file)?) {
+            output.push_str(&format!("{name}:{line}: Zsh-only {construct}\n"));
+        }
+    }
+    let docs = PluginDocs::load(plugin)?;
+    let plugin_file = plugin.plugin_file();
+    let plugin_file = plugin_file
+        .strip_prefix(plugin.root())
+        .unwrap_or(&plugin_file)
+        .display();
+    for function in docs.plugin().functions().filter(|f| f.is_public()) {
+        output.push_str(&format!(
+            "{plugin_file}: function '{}' is not available from Bash\n",
+            function.name()
+        ));
+    }
+    let modules = docs
+        .modules()
+        .map(|(name, doc)| (format!("{P_MODULES_DIR}/{name}.zsh"), doc));
+    for (file, doc) in [(plugin_file.to_string(), docs.plugin())]
+        .into_iter()
+        .chain(modules)
+    {
+        for alias in doc.aliases() {
+            output.push_str(&format!(
+                "{file}: alias '{}' is not available from Bash\n",
+                alias.name()
+            ));
+        }
+    }
+    Ok(if output.is_empty() {
+        Outcome::Passed
+    } else {
+        Outcome::Failed(output)
+    })
+}
+
+/// Zsh-only constructs, each described for the report, that Bash either cannot
+/// parse or silently treats differently.
+static ZSH_ONLY_SYNTAX: LazyLock<Vec<(&'static str, Regex)>> = LazyLock::new(|| {
+    [
+        ("'typeset -g'", r"\btypeset\s+-\w*g"),
+        (
+            "parameter modifier, e.g. '${0:h}'",
+            r"\$\{[#^=~+]*[\w@*]+(\[[^\]]*\])?(:[aAcehlPqQrtu])+\}",
+        ),
+        ("parameter flag, e.g. '${(%)...}'", r"\$\{\([^)]*\)"),
+        ("subscript flag, e.g. '[(r)...]'", r"\[\([a-zA-Z]+\)"),
+        (
+            "glob qualifier, e.g. '*(.N)'",
+            r"[\w*?\]]\((?:[./@=*%NDL^-]|[oO][nmLaAcd]|:[a-zA-Z]|\[[\d,-]+\])+\)",
+        ),
+        ("'read -A'", r"\bread\s+(-\w+\s+)*-\w*A"),
+        (
+            "builtin, e.g. 'autoload'",
+            r"(^|[;&|({]|\b(then|do|else)\b)\s*(builtin\s+)?(autoload|unfunction|whence|zle|bindkey|zstyle|setopt|unsetopt|zmodload|add-zsh-hook|print|compdef|zparseopts|zcompile)(\s|;|$)",
+        ),
+    ]
+    .into_iter()
+    .map(|(construct, pattern)| (construct, Regex::new(pattern).expect("invalid pattern")))
+    .collect()
+});
+
+fn run_tool(root: &Path, tool: &str, args: &[&std::ffi::OsStr]) -> Outcome {
+    match Command::new(tool).args(args).current_dir(root).output() {
+        Ok(output) if output.status.success() => Outcome::Passed,
+        Ok(output) => Outcome::Failed(format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )),
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            Outcome::Skipped(format!("'{tool}' is not installed"))
+        }
+        Err(e) => Outcome::Failed(e.to_string()),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for CheckStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Passed => "passed",
+                Self::Failed => "failed",
+                Self::Skipped => "skipped",
+            }
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_tool_is_skipped() {
+        assert_eq!(
+            run_tool(Path::new("."), "zsh-plugin-no-such-tool", &[]),
+            Outcome::Skipped("'zsh-plugin-no-such-tool' is not installed".to_string())
+        );
+    }
+
+    #[test]
+    fn find_zsh_only_syntax() {
+        let content = r#"# ${0:h} in a comment
+typeset -gA EXAMPLE
+EXAMPLE[_DIR]="${0:h}"
+print -P "${(%):-%N}"
+for fn in ${dir}/*(.:t); do autoload -Uz ${fn}; done
+local -a newest=( ${dir}/*(.Nom[1]) )
+local name="${1:-default}" count="${#items[@]}"
+printf 'file(s) %s\n' "$(basename "${name}")"
+"#;
+        assert_eq!(
+            zsh_only_syntax(content),
+            vec![
+                (2, "'typeset -g'"),
+                (3, "parameter modifier, e.g. '${0:h}'"),
+                (4, "parameter flag, e.g. '${(%)...}'"),
+                (4, "builtin, e.g. 'autoload'"),
+                (5, "glob qualifier, e.g. '*(.N)'"),
+                (5, "builtin, e.g. 'autoload'"),
+                (6, "glob qualifier, e.g. '*(.N)'"),
+            ]
+        );
+    }
+}
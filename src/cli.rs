@@ -1,6 +1,34 @@
-use crate::{command::OnceCommand, error::Error, name::Name, templates::init_new_plugin};
+use crate::{
+    check::check_plugin,
+    command::OnceCommand,
+    compile::compile_plugin,
+    config::Config,
+    dependencies::{add_dependencies, remove_dependencies},
+    doc::generate_docs,
+    environment::{EnvVar, add_env_vars},
+    error::Error,
+    export::{ReleaseSource, export_brew, export_nix},
+    install::{PluginManager, install_plugin, uninstall_plugin},
+    manifest::{show_manifest, sync_manifest},
+    name::Name,
+    package::package_plugin,
+    plugin::Plugin,
+    readme::sync_readme,
+    registry::{list_plugins, search_plugins},
+    repository::RemoteRepository,
+    requirements::{CommandRequirement, OnMissing, add_requirements, remove_requirements},
+    settings::{Setting, SettingType, add_setting},
+    shell_hooks::{ShellHook, add_hooks},
+    shim::{ShimShell, export_shim},
+    templates::{add_new_module, init_new_plugin, install_hooks},
+    test::test_plugin,
+    upgrade::upgrade_plugin,
+    version::{Version, bump_version, show_version},
+    widgets::{Keymap, Widget, add_widget},
+    workspace::{P_WORKSPACE, run_in_workspace},
+};
 use clap::{Parser, Subcommand, ValueEnum};
-use std::process::ExitCode;
+use std::{ffi::OsString, path::PathBuf, process::ExitCode};
 use tracing::{error, level_filters::LevelFilter};
 use tracing_subscriber::filter::EnvFilter;
 
@@ -68,7 +96,657 @@ pub(crate) enum Commands {
     ///
     /// 13. A file `README.md` containing only a basic skeleton. Generation will be
     ///     skipped if the `no-readme` is set.
-    Init(InitCommand),
+    ///
+    /// 14. If the option `add-modules` is set, a directory `modules` with a file
+    ///     `MODULE.zsh` for each named module.
+    Init(Box<InitCommand>),
+
+    /// Add new components to an existing plugin
+    Add(AddCommand),
+
+    /// Generate documentation for an existing plugin
+    ///
+    /// Documentation is generated from the shdoc-style annotations, such as
+    /// `@description` and `@arg`, in the plugin file, any modules, and any
+    /// autoloaded functions. The shdoc tool itself is not required.
+    ///
+    /// 1. The file `index.md` documents the main plugin file.
+    ///
+    /// 2. A file `MODULE.md` documents each file in the `modules` directory.
+    ///
+    /// 3. The file `functions.md` documents all functions in the `functions`
+    ///    directory.
+    ///
+    /// Alternatively, man pages may be generated for the plugin and each of
+    /// its public functions into `man/man1`, along with pages for Zsh's
+    /// `run-help` into `help`.
+    Doc(DocCommand),
+
+    /// Maintain the generated sections of an existing plugin's README
+    Readme(ReadmeCommand),
+
+    /// Show or change the version of an existing plugin
+    Version(VersionCommand),
+
+    /// Check the syntax, lint, README and manifest of an existing plugin
+    ///
+    /// Each Zsh source is parsed with 'zsh -n', all sources are linted with
+    /// shellcheck, the generated sections of the README are compared with
+    /// the plugin's functions and aliases, and any 'plugin.toml' manifest is
+    /// compared with the plugin's content. With '--bash' the autoload functions
+    /// the Bash wrapper sources are also searched for Zsh-only syntax. Checks are
+    /// skipped if the tool they require is not installed.
+    Check(CheckCommand),
+
+    /// Run the tests of an existing plugin
+    ///
+    /// The ShellSpec tests are run by 'make test', the 'test' target of the
+    /// plugin's generated 'Makefile', in the plugin's directory.
+    Test(TestCommand),
+
+    /// Upgrade an existing plugin to the current version of this tool
+    ///
+    /// The generated sections of the README are rewritten, the 'plugin.toml'
+    /// manifest is created or brought up to date and this version of the tool
+    /// recorded as its generator, and any generated blocks missing from the
+    /// plugin file are reported, to be added by hand.
+    Upgrade(UpgradeCommand),
+
+    /// Manage the Git hooks of an existing plugin
+    Hooks(HooksCommand),
+
+    /// Manage the plugins an existing plugin depends on
+    Deps(DepsCommand),
+
+    /// Manage the external commands an existing plugin requires
+    Requires(RequiresCommand),
+
+    /// Show or update the 'plugin.toml' manifest of an existing plugin
+    ///
+    /// The manifest records the plugin's name, variable prefix, version,
+    /// license, authors, dependencies, the functions, aliases and completions
+    /// it provides, and the components it includes, for use by other tools.
+    /// It is written by 'init' and kept up to date by the commands that change
+    /// a plugin.
+    Manifest(ManifestCommand),
+
+    /// Compile the sources of an existing plugin to Zsh word code
+    ///
+    /// The plugin file and each module are compiled with 'zcompile' to a '.zwc'
+    /// file, which Zsh uses in place of the source while it is up to date, and the
+    /// autoload functions to a single digest, 'functions.zwc'.
+    Compile(CompileCommand),
+
+    /// Package an existing plugin as a versioned release tarball
+    ///
+    /// The plugin file, Bash wrapper, modules, functions, scripts, license,
+    /// README and documentation, but no tests, CI or other development files,
+    /// are written to 'NAME-VERSION.tar.gz' with a 'SHA256SUMS' file and the
+    /// 'plugin.toml' manifest. The checksum of the archive itself is written to
+    /// 'NAME-VERSION.tar.gz.sha256', e.g. for use in a Homebrew formula.
+    Package(PackageCommand),
+
+    /// Export package manager recipes for an existing plugin
+    Export(ExportCommand),
+
+    /// Install an existing plugin into your own shell
+    ///
+    /// The plugin is linked, or with '--copy' copied, into the plugins directory
+    /// and a marked block that loads it is added to '.zshrc', in '$ZDOTDIR' or
+    /// '$HOME'. The file '.zshrc' is backed up before it is changed.
+    Install(InstallCommand),
+
+    /// Uninstall a plugin installed with 'install'
+    ///
+    /// The plugin is removed from the plugins directory, and its block from
+    /// '.zshrc', which is backed up before it is changed.
+    Uninstall(UninstallCommand),
+
+    /// List the plugins found below the plugin roots
+    ///
+    /// The roots are given with '--root', or by the 'plugin-roots' key of the
+    /// configuration file, otherwise the current directory is used. Each
+    /// plugin's name, version, description, number of functions and the
+    /// combined outcome of the 'check' command's checks are shown.
+    List(ListCommand),
+
+    /// Search the plugins found below the plugin roots
+    ///
+    /// A plugin matches if its name, description, or the name of any of its
+    /// functions, contains the search term, ignoring case.
+    Search(SearchCommand),
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct CheckCommand {
+    /// Also search the files the Bash wrapper sources for Zsh-only syntax, and
+    /// report the functions and aliases only the plugin file provides.
+    #[arg(long, short = 'b', action)]
+    bash: bool,
+
+    /// The directory containing the plugin to check.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+
+    /// Run against every plugin of the workspace in, or above, the current directory.
+    #[arg(long, short = 'w', action, conflicts_with = "plugin_dir")]
+    workspace: bool,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct TestCommand {
+    /// The directory containing the plugin to test.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+
+    /// Run against every plugin of the workspace in, or above, the current directory.
+    #[arg(long, short = 'w', action, conflicts_with = "plugin_dir")]
+    workspace: bool,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct UpgradeCommand {
+    /// The directory containing the plugin to upgrade.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+
+    /// Run against every plugin of the workspace in, or above, the current directory.
+    #[arg(long, short = 'w', action, conflicts_with = "plugin_dir")]
+    workspace: bool,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct CompileCommand {
+    /// The directory containing the plugin to compile.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct PackageCommand {
+    /// The directory to write the package into, by default the plugin's 'dist'
+    /// directory.
+    #[arg(long, short = 'o')]
+    output_dir: Option<PathBuf>,
+
+    /// The directory containing the plugin to package.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+
+    /// Run against every plugin of the workspace in, or above, the current directory.
+    #[arg(long, short = 'w', action, conflicts_with = "plugin_dir")]
+    workspace: bool,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct ExportCommand {
+    #[command(subcommand)]
+    cmd: ExportCommands,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub(crate) enum ExportCommands {
+    /// Render a Homebrew formula for the plugin's release tarball
+    ///
+    /// The formula installs the plugin into 'share/zsh-NAME', with caveats
+    /// explaining how to source it from '~/.zshrc'.
+    Brew(ExportBrewCommand),
+
+    /// Render a Nix derivation, or Home Manager configuration, for the plugin
+    ///
+    /// The derivation installs the plugin's release tarball into
+    /// 'share/zsh-NAME'. With '--home-manager' a configuration is rendered
+    /// instead that adds the plugin from this derivation to
+    /// 'programs.zsh.plugins'.
+    Nix(ExportNixCommand),
+
+    /// Port the plugin's public commands and aliases to Fish or POSIX sh
+    ///
+    /// The plugin's 'bin' directory is added to 'PATH', and each public function
+    /// or alias that runs a single simple command is ported; any other is
+    /// reported as not ported. Fish gets a 'conf.d/NAME.fish' file and a
+    /// 'functions' directory, sh a single 'NAME.sh' file to source.
+    Shim(ExportShimCommand),
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct ExportSourceArgs {
+    /// The URL of the release tarball, by default the asset of the release tagged
+    /// 'vVERSION' in the plugin's repository.
+    #[arg(long, short = 'u')]
+    url: Option<String>,
+
+    /// The SHA-256 checksum of the release tarball, by default read from the
+    /// checksum file written by the 'package' command.
+    #[arg(long, short = 's')]
+    sha256: Option<String>,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct ExportBrewCommand {
+    #[command(flatten)]
+    source: ExportSourceArgs,
+
+    /// Write the formula to this file rather than to stdout.
+    #[arg(long, short = 'o')]
+    output: Option<PathBuf>,
+
+    /// The directory containing the plugin.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct ExportNixCommand {
+    #[command(flatten)]
+    source: ExportSourceArgs,
+
+    /// Render a Home Manager configuration that uses the derivation.
+    #[arg(long, action)]
+    home_manager: bool,
+
+    /// Write the expression to this file rather than to stdout.
+    #[arg(long, short = 'o')]
+    output: Option<PathBuf>,
+
+    /// The directory containing the plugin.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct ExportShimCommand {
+    /// The shell to port the plugin to.
+    #[arg(long, short = 's', value_enum)]
+    shell: ShimShell,
+
+    /// The directory to write the port into, by default the plugin's 'dist/SHELL'
+    /// directory.
+    #[arg(long, short = 'o')]
+    output_dir: Option<PathBuf>,
+
+    /// The directory containing the plugin.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct InstallCommand {
+    /// Copy the plugin's files rather than linking to its directory.
+    #[arg(long, short = 'c', action)]
+    copy: bool,
+
+    /// Force replacement of an existing file or directory of the same name.
+    #[arg(long, short = 'f', action)]
+    force: bool,
+
+    /// How '.zshrc' loads the plugin, by default the 'plugin-manager' key of the
+    /// configuration file, or sourcing the plugin file directly.
+    #[arg(long, short = 'm', value_name = "MANAGER")]
+    manager: Option<PluginManager>,
+
+    /// The directory to install the plugin into, by default the 'plugins-dir' key
+    /// of the configuration file.
+    #[arg(long, value_name = "DIR")]
+    plugins_dir: Option<PathBuf>,
+
+    /// The directory containing the plugin to install.
+    path: PathBuf,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct UninstallCommand {
+    /// Force removal of a file or directory of the same name that does not
+    /// contain the plugin.
+    #[arg(long, short = 'f', action)]
+    force: bool,
+
+    /// The directory the plugin was installed into, by default the 'plugins-dir'
+    /// key of the configuration file.
+    #[arg(long, value_name = "DIR")]
+    plugins_dir: Option<PathBuf>,
+
+    /// The name of the plugin to uninstall, as given to 'init' or as installed.
+    name: Name,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct ListCommand {
+    /// Write the list as JSON.
+    #[arg(long, action)]
+    json: bool,
+
+    /// A directory to discover plugins in, may be repeated.
+    #[arg(long, short = 'r', value_name = "DIR")]
+    root: Vec<PathBuf>,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct SearchCommand {
+    /// Write the matching plugins as JSON.
+    #[arg(long, action)]
+    json: bool,
+
+    /// A directory to discover plugins in, may be repeated.
+    #[arg(long, short = 'r', value_name = "DIR")]
+    root: Vec<PathBuf>,
+
+    /// The text to search for.
+    term: String,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct HooksCommand {
+    #[command(subcommand)]
+    cmd: HooksCommands,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub(crate) enum HooksCommands {
+    /// Write the Git hooks into '.githooks' and configure Git to use them
+    ///
+    /// The pre-commit hook runs 'zsh -n' and shellcheck on staged files, and
+    /// then the 'check' command of this tool. The Git configuration
+    /// 'core.hooksPath' is set so that the hooks, which are committed with the
+    /// plugin, are used.
+    Install(HooksInstallCommand),
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct HooksInstallCommand {
+    /// Force over-writing of existing hooks.
+    #[arg(long, short = 'f', action)]
+    force: bool,
+
+    /// Also write a pre-push hook that runs the shellspec tests.
+    #[arg(long, action)]
+    pre_push: bool,
+
+    /// The directory containing the plugin.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct DepsCommand {
+    #[command(subcommand)]
+    cmd: DepsCommands,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub(crate) enum DepsCommands {
+    /// Add plugins that must be loaded before this one
+    ///
+    /// The dependencies are recorded in 'plugin.toml', and the generated block
+    /// between the 'zsh-plugin:begin:dependencies' and 'zsh-plugin:end:dependencies'
+    /// marker comments in the plugin file is rewritten. With zplugins this
+    /// declares the dependencies to the plugin manager, otherwise it checks each
+    /// dependency's plugin hash is defined and fails to load if not.
+    Add(DepsUpdateCommand),
+
+    /// Remove plugins from those this one depends on
+    Remove(DepsUpdateCommand),
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct DepsUpdateCommand {
+    /// The directory containing the plugin to modify.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+
+    /// The names of the plugins depended on.
+    #[arg(required = true)]
+    names: Vec<Name>,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct RequiresCommand {
+    #[command(subcommand)]
+    cmd: RequiresCommands,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub(crate) enum RequiresCommands {
+    /// Add, or replace, commands that the plugin checks for when it loads
+    ///
+    /// The requirements are recorded in 'plugin.toml', listed in the README, and
+    /// the generated block between the 'zsh-plugin:begin:requirements' and
+    /// 'zsh-plugin:end:requirements' marker comments in the plugin's init
+    /// function is rewritten.
+    Add(RequiresAddCommand),
+
+    /// Remove commands from those the plugin requires
+    Remove(RequiresRemoveCommand),
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct RequiresAddCommand {
+    /// What the plugin does if a command is missing, or too old.
+    #[arg(long, short = 'o', value_name = "ACTION", default_value = "warn")]
+    on_missing: OnMissing,
+
+    /// The directory containing the plugin to modify.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+
+    /// The commands required, each either 'NAME' or 'NAME>=VERSION'.
+    #[arg(required = true)]
+    commands: Vec<CommandRequirement>,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct RequiresRemoveCommand {
+    /// The directory containing the plugin to modify.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+
+    /// The names of the commands no longer required.
+    #[arg(required = true)]
+    names: Vec<String>,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct ManifestCommand {
+    #[command(subcommand)]
+    cmd: ManifestCommands,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub(crate) enum ManifestCommands {
+    /// Print the manifest, derived from the plugin if it has none
+    Show(ManifestShowCommand),
+
+    /// Write the manifest, or update it from the plugin's current content
+    ///
+    /// Everything except the dependencies, settings and the generator section is derived
+    /// from the plugin file's header tags, its sources, and the files present.
+    Sync(ManifestSyncCommand),
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct ManifestShowCommand {
+    /// The directory containing the plugin.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct ManifestSyncCommand {
+    /// Do not update the manifest, fail if it is out of date.
+    #[arg(long, short = 'c', action)]
+    check: bool,
+
+    /// The directory containing the plugin.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct ReadmeCommand {
+    #[command(subcommand)]
+    cmd: ReadmeCommands,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub(crate) enum ReadmeCommands {
+    /// Update the README "Functions" and "Aliases" sections
+    ///
+    /// The public functions defined in the plugin file, modules and the
+    /// `functions` directory, and the aliases defined with the plugin's
+    /// `define_alias` function, are listed along with their `@description`
+    /// text. Only the content between the `zsh-plugin:begin` and
+    /// `zsh-plugin:end` marker comments in the README is replaced.
+    Sync(ReadmeSyncCommand),
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct ReadmeSyncCommand {
+    /// Do not update the README, fail if it is out of date.
+    ///
+    /// This is useful in CI workflows to ensure the README is kept up to date.
+    #[arg(long, short = 'c', action)]
+    check: bool,
+
+    /// The directory containing the plugin to update.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct AddCommand {
+    #[command(subcommand)]
+    cmd: AddCommands,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub(crate) enum AddCommands {
+    /// Add a new module to the plugin
+    ///
+    /// Creates the file `modules/MODULE.zsh`, and the `modules` directory if
+    /// necessary. Modules are sourced by the plugin's `_plugin_init` function
+    /// in the lexical order of their file names and any functions they define
+    /// are removed when the plugin is unloaded.
+    Module(AddModuleCommand),
+
+    /// Add environment variables that the plugin sets while it is loaded
+    ///
+    /// Code is appended to the generated blocks between the 'zsh-plugin:begin:env-save'
+    /// and 'zsh-plugin:end:env-save' marker comments in the plugin's init function,
+    /// to save each variable's state and export its default value, and between the
+    /// 'env-restore' markers in the unload function to restore it.
+    Env(AddEnvCommand),
+
+    /// Add functions that are registered with Zsh hooks while the plugin is loaded
+    ///
+    /// A stub function, 'NAME_HOOK_hook', is added for each hook between the
+    /// 'zsh-plugin:begin:hook-functions' and 'zsh-plugin:end:hook-functions' marker
+    /// comments. It is registered with 'add-zsh-hook' between the 'hooks-add' markers
+    /// in the plugin's init function, and removed between the 'hooks-remove' markers
+    /// in the unload function.
+    Hook(AddHookCommand),
+
+    /// Add a line editor widget, optionally bound to a key sequence
+    ///
+    /// A stub function, 'NAME_WIDGET_widget', is added between the
+    /// 'zsh-plugin:begin:widget-functions' and 'zsh-plugin:end:widget-functions'
+    /// marker comments. The widget is defined with 'zle -N', and bound with 'bindkey',
+    /// between the 'widgets-add' markers in the plugin's init function. The unload
+    /// function restores the previous key binding and deletes the widget with 'zle -D',
+    /// between the 'widgets-remove' markers.
+    Widget(AddWidgetCommand),
+
+    /// Add a setting that users of the plugin configure with 'zstyle'
+    ///
+    /// A lookup helper, 'NAME_setting_SETTING', is added between the
+    /// 'zsh-plugin:begin:setting-functions' and 'zsh-plugin:end:setting-functions'
+    /// marker comments. The setting is documented in the "Public Variables" section
+    /// of the plugin file's header and the README, and recorded in 'plugin.toml'.
+    /// If the plugin uses shellspec, tests for the default and an overridden value
+    /// are added to 'spec/NAME_spec.sh'.
+    Setting(AddSettingCommand),
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct AddSettingCommand {
+    /// The directory containing the plugin to modify.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+
+    /// The type of the setting's value.
+    #[arg(
+        long = "type",
+        short = 't',
+        value_name = "TYPE",
+        default_value = "string"
+    )]
+    kind: SettingType,
+
+    /// The value used when the setting is not set, the elements of an array are comma-separated.
+    #[arg(long, short = 'd')]
+    default: Option<String>,
+
+    /// A short description of the setting, for the documentation.
+    #[arg(long, short = 'D')]
+    description: Option<String>,
+
+    /// The name of the setting, used as the style name with 'zstyle'.
+    name: Name,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct AddWidgetCommand {
+    /// The directory containing the plugin to modify.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+
+    /// Bind the widget to this key sequence, in 'bindkey' notation, e.g. '^Xg'.
+    #[arg(long, short = 'b', value_name = "KEYSEQ")]
+    bind: Option<String>,
+
+    /// The keymap to add the key binding to.
+    #[arg(long, short = 'm', default_value = "main", requires = "bind")]
+    keymap: Keymap,
+
+    /// The name of the widget.
+    name: Widget,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct AddHookCommand {
+    /// The directory containing the plugin to modify.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+
+    /// The hooks to add functions for.
+    #[arg(required = true)]
+    hooks: Vec<ShellHook>,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct AddEnvCommand {
+    /// The directory containing the plugin to modify.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+
+    /// The variables, each either 'NAME' or 'NAME=DEFAULT'.
+    #[arg(required = true)]
+    vars: Vec<EnvVar>,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct AddModuleCommand {
+    /// Force over-writing of existing files.
+    #[arg(long, short = 'f', action)]
+    force: bool,
+
+    /// The directory containing the plugin to modify.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+
+    /// The name of the new module.
+    ///
+    /// Module names follow the same rules as plugin names.
+    name: Name,
 }
 
 #[derive(Clone, Debug, Parser)]
@@ -110,9 +788,9 @@ pub(crate) struct InitCommand {
     /// Do not include support for documentation generation with the
     /// shdoc tool.
     ///
-    /// Add documentation steps to the 'Makefile'. The plugin file and
-    /// all functions will include basic shelldoc documentation whether
-    /// this flag is set or not.
+    /// Add documentation steps to the 'Makefile', using the 'doc' command of
+    /// this tool. The plugin file and all functions will include basic shdoc
+    /// documentation whether this flag is set or not.
     #[arg(long, short = 'D', action, conflicts_with = "template")]
     no_shell_doc: bool,
 
@@ -134,95 +812,1196 @@ pub(crate) struct InitCommand {
     #[arg(long, short = 'G', action, conflicts_with = "template")]
     no_git_init: bool,
 
+    /// Set the name of the initial branch of the new Git repository.
+    ///
+    /// The generated Github Actions workflow is triggered by pushes and pull
+    /// requests to this branch.
+    #[arg(long, short = 'b', default_value = "main")]
+    initial_branch: String,
+
+    /// Do not create an initial commit in the new Git repository.
+    ///
+    /// By default all generated files are staged and committed using the
+    /// author configured for Git, or the repository owner if none is configured.
+    #[arg(long, action)]
+    no_initial_commit: bool,
+
+    /// Do not add an 'origin' remote to the new Git repository.
+    ///
+    /// By default the remote is the clone URL of the plugin's repository, see
+    /// '--repository'.
+    #[arg(long, action)]
+    no_git_remote: bool,
+
+    /// Add Git hooks, in a '.githooks' sub-directory, that check the plugin
+    /// before each commit.
+    ///
+    /// The pre-commit hook runs 'zsh -n' and shellcheck on staged files, and then
+    /// the 'check' command of this tool. The Git configuration 'core.hooksPath'
+    /// of the new repository is set to use this directory.
+    #[arg(long, short = 'k', action)]
+    add_hooks: bool,
+
+    /// Also add a Git pre-push hook that runs the shellspec tests.
+    #[arg(long, action, requires = "add_hooks")]
+    add_pre_push_hook: bool,
+
     /// Do not include a '.github' sub-directory.
     ///
-    /// By default the created plugin includes a '.github/worflows' directory
-    /// with a file 'shell.yml' that defines a Github Actions workflow. Note
-    /// that if both 'no-shell-check' and 'no-shell_test' options are set the
-    /// workflow file is not created as it would effectively be a no-op.
-    #[arg(long, short = 'H', action, conflicts_with = "template")]
+    /// This is equivalent to '--ci none'.
+    #[arg(long, short = 'H', action, conflicts_with_all = ["template", "ci"])]
     no_github_dir: bool,
 
-    /// Do not include a README.md file.
-    #[arg(long, short = 'R', action, conflicts_with = "template")]
-    no_readme: bool,
+    /// Select the CI service for which a workflow or pipeline is generated.
+    ///
+    /// By default the created plugin includes a Github Actions workflow in
+    /// '.github/worflows/shell.yml'. Note that if both 'no-shell-check' and
+    /// 'no-shell-spec' options are set no workflow file is created as it
+    /// would effectively be a no-op.
+    #[arg(long, value_enum)]
+    ci: Option<CiTarget>,
+
+    /// Set the URL of the plugin's hosted repository.
+    ///
+    /// This is used for the Git remote and all URLs in 'README.md', the CI
+    /// badges and the plugin file. Both web and clone URLs are accepted, e.g.
+    /// 'https://codeberg.org/me/zsh-NAME-plugin' or 'git@gitlab.com:me/zsh-NAME-plugin.git'.
+    /// If not set the 'origin' remote of an existing Git repository in the
+    /// target directory is used, otherwise 'https://HOST/$USER/zsh-NAME-plugin'
+    /// where HOST is 'github.com', 'gitlab.com' or, for Forgejo, 'codeberg.org'
+    /// depending on the CI service.
+    #[arg(long, short = 'r', value_name = "URL")]
+    repository: Option<RemoteRepository>,
+
+    /// Set the URL of the plugin's homepage, if different from the repository.
+    #[arg(long, value_name = "URL")]
+    homepage: Option<String>,
+
+    /// Set the full name of the plugin's author.
+    ///
+    /// If not set the 'author' key of the configuration file is used, otherwise
+    /// Git's 'user.name'. The plugin header omits the '@author' and '@copyright'
+    /// tags if no author is known.
+    #[arg(long)]
+    author: Option<String>,
+
+    /// Set the email address of the plugin's author.
+    ///
+    /// If not set the 'email' key of the configuration file is used, otherwise
+    /// Git's 'user.email'.
+    #[arg(long)]
+    email: Option<String>,
+
+    /// Set the license of the plugin as an SPDX expression, e.g. 'MIT OR Apache-2.0'.
+    ///
+    /// If not set the 'license' key of the configuration file is used. The plugin
+    /// header omits the '@license' tag if no license is known.
+    #[arg(long)]
+    license: Option<String>,
+
+    /// Set the initial semantic version of the plugin.
+    ///
+    /// If not set the 'version' key of the configuration file is used, otherwise
+    /// '0.1.0'.
+    #[arg(long)]
+    version: Option<Version>,
+
+    /// Do not include a README.md file.
+    #[arg(long, short = 'R', action, conflicts_with = "template")]
+    no_readme: bool,
+
+    /// Do not include support for testing using the shellspec tool.
+    ///
+    /// Add testing steps to the 'Makefile' and 'shell.yml' (Github Action) files.
+    #[arg(long, short = 'S', action, conflicts_with = "template")]
+    no_shell_spec: bool,
+
+    /// Add a 'modules' sub-directory with a file for each named module.
+    ///
+    /// Modules allow a larger plugin to be split across multiple files, each
+    /// module is sourced by the plugin's init function and any functions it
+    /// defines are tracked for unloading.
+    #[arg(long, short = 'm', value_delimiter = ',')]
+    add_modules: Vec<Name>,
+
+    /// Declare another plugin that must be loaded before this one, may be repeated.
+    ///
+    /// With zplugins the dependency is declared to the plugin manager, otherwise
+    /// the plugin fails to load, with a clear message, unless the dependency's
+    /// plugin hash is defined. Dependencies are recorded in 'plugin.toml' and
+    /// maintained with the 'deps' command.
+    #[arg(long, value_name = "PLUGIN")]
+    depends_on: Vec<Name>,
+
+    /// Check for an external command when the plugin loads, may be repeated.
+    ///
+    /// The value is either 'NAME' or 'NAME>=VERSION', in which case the first
+    /// version number in the output of 'NAME --version' is also checked.
+    /// Requirements are recorded in 'plugin.toml', listed in the README, and
+    /// maintained with the 'requires' command.
+    #[arg(long, value_name = "CMD")]
+    requires_command: Vec<CommandRequirement>,
+
+    /// What the plugin does if a required command is missing, or too old.
+    #[arg(
+        long,
+        value_name = "ACTION",
+        default_value = "warn",
+        requires = "requires_command"
+    )]
+    on_missing_command: OnMissing,
+
+    /// Export an environment variable while the plugin is loaded, may be repeated.
+    ///
+    /// The value is either 'NAME' or 'NAME=DEFAULT', the default is only used if
+    /// the variable is not already set. The variable's previous state, unset, set,
+    /// or exported, is saved by the init function and restored on unload.
+    #[arg(long, short = 'e', value_name = "VAR")]
+    env: Vec<EnvVar>,
+
+    /// Add a function registered with a Zsh hook while the plugin is loaded, may be repeated.
+    #[arg(long, value_name = "HOOK")]
+    add_hook: Vec<ShellHook>,
+
+    /// Do not use the `zplugins` plugin manager for support functions.
+    ///
+    /// The zplugins manager provides a lot of features such as autoloading
+    /// functions, adding certain directories to 'path' and 'fpath' if present
+    /// and tracking global variables and aliases so they are automatically
+    /// cleaned up when a plugin is unloaded. Without this framework the
+    /// generated plugin has a lot of code to emulate some of these features.
+    #[arg(long, short = 'Z')]
+    use_plain_plugins: bool,
+
+    /// Short description of the plugin.
+    ///
+    /// This description is added to the plugin source and 'README.md' files.
+    #[arg(long, short = 'd')]
+    description: Option<String>,
+
+    /// The name of the new plugin.
+    ///
+    /// Plugin names are restricted to a "safe" subset corresponding to the
+    /// following regular expression `\[a-zA-Z\]\[a-zA-Z0-9_-\]``.
+    name: Name,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct VersionCommand {
+    #[command(subcommand)]
+    cmd: VersionCommands,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub(crate) enum VersionCommands {
+    /// Show the current version, from the `@version` tag in the plugin file
+    Show(VersionShowCommand),
+
+    /// Increment the version and record a new section of changes
+    ///
+    /// The `@version` tag in the plugin file is updated and a new section,
+    /// headed with the version and date, is added to `CHANGELOG.md` or, if
+    /// that file does not exist, to the "Changes" section of `README.md`.
+    Bump(VersionBumpCommand),
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct VersionShowCommand {
+    /// The directory containing the plugin.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct VersionBumpCommand {
+    /// Derive the new section's entries from conventional commits.
+    ///
+    /// Commits since the tag of the current version, 'vMAJOR.MINOR.PATCH', with
+    /// summaries such as 'feat: ...' or 'fix(scope): ...' are listed, grouped by
+    /// type.
+    #[arg(long, short = 'c', action)]
+    from_commits: bool,
+
+    /// Commit the changed files and create an annotated tag for the new version.
+    #[arg(long, short = 't', action)]
+    tag: bool,
+
+    /// The directory containing the plugin.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+
+    /// The part of the version to increment.
+    part: VersionPart,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ValueEnum)]
+pub(crate) enum VersionPart {
+    /// Increment the major version, for incompatible changes.
+    Major,
+
+    /// Increment the minor version, for compatible additions.
+    Minor,
+
+    /// Increment the patch version, for compatible fixes.
+    Patch,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct DocCommand {
+    /// The directory containing the plugin to document.
+    #[arg(long, short = 'p', default_value = ".")]
+    plugin_dir: PathBuf,
+
+    /// The format of the generated documentation.
+    #[arg(long, short = 'F', value_enum, default_value_t = DocFormat::Markdown)]
+    format: DocFormat,
+
+    /// The directory, relative to the plugin directory, to write documentation into.
+    ///
+    /// The default is 'doc' for Markdown, and the plugin directory itself for
+    /// man pages which are written into its 'man/man1' and 'help' sub-directories.
+    #[arg(long, short = 'o')]
+    output_dir: Option<PathBuf>,
+
+    /// Run against every plugin of the workspace in, or above, the current directory.
+    #[arg(long, short = 'w', action, conflicts_with = "plugin_dir")]
+    workspace: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ValueEnum)]
+pub(crate) enum DocFormat {
+    /// Markdown files, one per source file.
+    Markdown,
+
+    /// Section 1 man pages for the plugin and each public function, and
+    /// help pages for Zsh's `run-help`.
+    Man,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ValueEnum)]
+pub(crate) enum CiTarget {
+    /// A Github Actions workflow, '.github/workflows/shell.yml'.
+    Github,
+
+    /// A GitLab CI/CD pipeline, '.gitlab-ci.yml'.
+    Gitlab,
+
+    /// A Forgejo, or Gitea, Actions workflow, '.forgejo/workflows/shell.yml'.
+    Forgejo,
+
+    /// No CI workflow or pipeline.
+    #[value(name = "none")]
+    Disabled,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ValueEnum)]
+pub(crate) enum Template {
+    /// Minimal plugin structure.
+    ///
+    /// The generated plugin contains no binary or functions directories,
+    /// no Git directory, no GitHub workflows, and no support for aliases,
+    /// shellcheck or  shellspec.
+    Minimal,
+
+    /// Simple in-line function plugin structure.
+    ///
+    /// The generated plugin contains support for aliases, shellcheck and
+    /// shellspec, but all functions are defined in-line in the main plugin.
+    /// It does not include the binary or functions directories, or
+    /// GitHub workflows.
+    Simple,
+
+    /// Complete plugin structure with all optional components included.
+    Complete,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Command Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl OnceCommand for Cli {
+    type Output = ExitCode;
+    type Error = Error;
+
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        init_tracing(self.verbosity)?;
+        self.cmd.clone().execute()
+    }
+}
+
+impl OnceCommand for Commands {
+    type Output = ExitCode;
+    type Error = Error;
+
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        match self {
+            Commands::Init(init_command) => (*init_command).execute(),
+            Commands::Add(add_command) => add_command.execute(),
+            Commands::Doc(doc_command) => doc_command.execute(),
+            Commands::Readme(readme_command) => readme_command.execute(),
+            Commands::Version(version_command) => version_command.execute(),
+            Commands::Check(check_command) => check_command.execute(),
+            Commands::Test(test_command) => test_command.execute(),
+            Commands::Upgrade(upgrade_command) => upgrade_command.execute(),
+            Commands::Hooks(hooks_command) => hooks_command.execute(),
+            Commands::Deps(deps_command) => deps_command.execute(),
+            Commands::Requires(requires_command) => requires_command.execute(),
+            Commands::Manifest(manifest_command) => manifest_command.execute(),
+            Commands::Compile(compile_command) => compile_command.execute(),
+            Commands::Package(package_command) => package_command.execute(),
+            Commands::Export(export_command) => export_command.execute(),
+            Commands::Install(install_command) => install_command.execute(),
+            Commands::Uninstall(uninstall_command) => uninstall_command.execute(),
+            Commands::List(list_command) => list_command.execute(),
+            Commands::Search(search_command) => search_command.execute(),
+        }
+    }
+}
+
+impl OnceCommand for AddCommand {
+    type Output = ExitCode;
+    type Error = Error;
+
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result =
+            match self.cmd {
+                AddCommands::Module(cmd) => cmd.execute(),
+                AddCommands::Env(cmd) => Plugin::open(&cmd.plugin_dir)
+                    .and_then(|plugin| add_env_vars(&plugin, &cmd.vars)),
+                AddCommands::Hook(cmd) => {
+                    Plugin::open(&cmd.plugin_dir).and_then(|plugin| add_hooks(&plugin, &cmd.hooks))
+                }
+                AddCommands::Widget(cmd) => Plugin::open(&cmd.plugin_dir)
+                    .and_then(|plugin| add_widget(&plugin, &cmd.widget())),
+                AddCommands::Setting(cmd) => cmd.execute(),
+            };
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::PluginNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a plugin to modify.
+├─ Path: {path:?}
+└─ Help: Use the '--plugin-dir' option to select a directory containing exactly one '*.plugin.zsh' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::TargetExists { path }) => {
+                eprintln!(
+                    r#"Addition failed as the target file or directory already exists.
+├─ Path: {path:?}
+└─ Help: Use the '--force' option to overwrite existing files and directories."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::MarkerNotFound { path, marker }) => {
+                eprintln!(
+                    r#"Could not find the generated block to add to in the plugin file.
+├─ Path: {path:?}
+└─ Help: Add the marker comments, starting with '{marker}', to the plugin's lifecycle functions."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error adding to the plugin
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
+
+impl OnceCommand for DocCommand {
+    type Output = ExitCode;
+    type Error = Error;
+
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result = if self.workspace {
+            let mut args: Vec<OsString> = vec!["doc".into(), "--format".into()];
+            args.extend(
+                self.format
+                    .to_possible_value()
+                    .map(|value| value.get_name().into()),
+            );
+            if let Some(output_dir) = self.output_dir {
+                args.extend(["--output-dir".into(), output_dir.into()]);
+            }
+            run_in_workspace(&args)
+        } else {
+            let output_dir = self.output_dir.map(|dir| self.plugin_dir.join(dir));
+            Plugin::open(&self.plugin_dir)
+                .and_then(|plugin| generate_docs(&plugin, self.format, output_dir.as_deref()))
+        };
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::PluginNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a plugin to document.
+├─ Path: {path:?}
+└─ Help: Use the '--plugin-dir' option to select a directory containing exactly one '*.plugin.zsh' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::WorkspaceNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a workspace to document.
+├─ Path: {path:?}
+└─ Help: Run the command in, or below, a directory containing a '{P_WORKSPACE}' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error generating plugin documentation
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
+
+impl OnceCommand for ReadmeCommand {
+    type Output = ExitCode;
+    type Error = Error;
+
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result = match self.cmd {
+            ReadmeCommands::Sync(cmd) => {
+                Plugin::open(&cmd.plugin_dir).and_then(|plugin| sync_readme(&plugin, cmd.check))
+            }
+        };
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::PluginNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a plugin to update.
+├─ Path: {path:?}
+└─ Help: Use the '--plugin-dir' option to select a directory containing exactly one '*.plugin.zsh' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::MarkerNotFound { path, marker }) => {
+                eprintln!(
+                    r#"Could not find the generated section markers in the README.
+├─ Path: {path:?}
+└─ Help: Add the marker comments, starting with '{marker}', around the generated section."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error updating the plugin README
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
+
+impl OnceCommand for CompileCommand {
+    type Output = ExitCode;
+    type Error = Error;
+
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        match Plugin::open(&self.plugin_dir).and_then(|plugin| compile_plugin(&plugin)) {
+            Ok(code) => Ok(code),
+            Err(Error::PluginNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a plugin to compile.
+├─ Path: {path:?}
+└─ Help: Use the '--plugin-dir' option to select a directory containing exactly one '*.plugin.zsh' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::Compile { path, message }) => {
+                eprintln!(
+                    r#"
+Could not compile the plugin.
+├─ Path: {path:?}
+├─ Error: {message}
+└─ Help: Fix the errors reported by 'zcompile', or check that 'zsh' is installed."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error compiling the plugin
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
+
+impl OnceCommand for PackageCommand {
+    type Output = ExitCode;
+    type Error = Error;
+
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result = if self.workspace {
+            let mut args: Vec<OsString> = vec!["package".into()];
+            if let Some(output_dir) = self.output_dir {
+                args.extend(["--output-dir".into(), output_dir.into()]);
+            }
+            run_in_workspace(&args)
+        } else {
+            Plugin::open(&self.plugin_dir)
+                .and_then(|plugin| package_plugin(&plugin, self.output_dir.as_deref()))
+        };
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::PluginNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a plugin to package.
+├─ Path: {path:?}
+└─ Help: Use the '--plugin-dir' option to select a directory containing exactly one '*.plugin.zsh' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::WorkspaceNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a workspace to package.
+├─ Path: {path:?}
+└─ Help: Run the command in, or below, a directory containing a '{P_WORKSPACE}' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::InvalidVersion { value }) => {
+                eprintln!(
+                    r#"The plugin file does not contain a valid version.
+├─ Value: {value:?}
+└─ Help: Set the '@version' tag in the plugin file's header to a semantic version, e.g. '# @version 0.1.0'."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error packaging the plugin
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
+
+impl OnceCommand for CheckCommand {
+    type Output = ExitCode;
+    type Error = Error;
+
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result = if self.workspace {
+            let mut args = vec!["check".into()];
+            if self.bash {
+                args.push("--bash".into());
+            }
+            run_in_workspace(&args)
+        } else {
+            Plugin::open(&self.plugin_dir).and_then(|plugin| check_plugin(&plugin, self.bash))
+        };
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::PluginNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a plugin to check.
+├─ Path: {path:?}
+└─ Help: Use the '--plugin-dir' option to select a directory containing exactly one '*.plugin.zsh' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::WorkspaceNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a workspace to check.
+├─ Path: {path:?}
+└─ Help: Run the command in, or below, a directory containing a '{P_WORKSPACE}' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error checking the plugin
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
+
+impl OnceCommand for TestCommand {
+    type Output = ExitCode;
+    type Error = Error;
+
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result = if self.workspace {
+            run_in_workspace(&["test".into()])
+        } else {
+            Plugin::open(&self.plugin_dir).and_then(|plugin| test_plugin(&plugin))
+        };
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::PluginNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a plugin to test.
+├─ Path: {path:?}
+└─ Help: Use the '--plugin-dir' option to select a directory containing exactly one '*.plugin.zsh' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::WorkspaceNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a workspace to test.
+├─ Path: {path:?}
+└─ Help: Run the command in, or below, a directory containing a '{P_WORKSPACE}' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::TestsNotFound { path }) => {
+                eprintln!(
+                    r#"The plugin's 'Makefile' has no 'test' target.
+├─ Path: {path:?}
+└─ Help: Generate the plugin with ShellSpec support, or add a 'test' target that runs its tests."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error testing the plugin
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
+
+impl OnceCommand for UpgradeCommand {
+    type Output = ExitCode;
+    type Error = Error;
+
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result = if self.workspace {
+            run_in_workspace(&["upgrade".into()])
+        } else {
+            Plugin::open(&self.plugin_dir).and_then(|plugin| upgrade_plugin(&plugin))
+        };
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::PluginNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a plugin to upgrade.
+├─ Path: {path:?}
+└─ Help: Use the '--plugin-dir' option to select a directory containing exactly one '*.plugin.zsh' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::WorkspaceNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a workspace to upgrade.
+├─ Path: {path:?}
+└─ Help: Run the command in, or below, a directory containing a '{P_WORKSPACE}' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error upgrading the plugin
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
+
+impl OnceCommand for ExportCommand {
+    type Output = ExitCode;
+    type Error = Error;
+
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result = match self.cmd {
+            ExportCommands::Brew(cmd) => Plugin::open(&cmd.plugin_dir)
+                .and_then(|plugin| export_brew(&plugin, &cmd.source.into(), cmd.output.as_deref())),
+            ExportCommands::Nix(cmd) => Plugin::open(&cmd.plugin_dir).and_then(|plugin| {
+                export_nix(
+                    &plugin,
+                    &cmd.source.into(),
+                    cmd.home_manager,
+                    cmd.output.as_deref(),
+                )
+            }),
+            ExportCommands::Shim(cmd) => Plugin::open(&cmd.plugin_dir)
+                .and_then(|plugin| export_shim(&plugin, cmd.shell, cmd.output_dir.as_deref())),
+        };
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::PluginNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a plugin to export.
+├─ Path: {path:?}
+└─ Help: Use the '--plugin-dir' option to select a directory containing exactly one '*.plugin.zsh' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::PackageNotFound { path }) => {
+                eprintln!(
+                    r#"Could not read the checksum of the plugin's release tarball.
+├─ Path: {path:?}
+└─ Help: Run 'zsh-plugin package' first, or use the '--sha256' option."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::MissingMetadata { tag }) => {
+                eprintln!(
+                    r#"Could not determine the URL of the plugin's release tarball.
+├─ Tag: '@{tag}'
+└─ Help: Set the '@{tag}' tag in the plugin file's header, or use the '--url' option."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::InvalidVersion { value }) => {
+                eprintln!(
+                    r#"The plugin file does not contain a valid version.
+├─ Value: {value:?}
+└─ Help: Set the '@version' tag in the plugin file's header to a semantic version, e.g. '# @version 0.1.0'."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error exporting the plugin
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
+
+impl OnceCommand for InstallCommand {
+    type Output = ExitCode;
+    type Error = Error;
+
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result = Config::load().and_then(|config| {
+            let plugin = Plugin::open(&self.path)?;
+            install_plugin(
+                &plugin,
+                &plugins_dir(self.plugins_dir, &config)?,
+                self.manager.or(config.plugin_manager()).unwrap_or_default(),
+                self.copy,
+                self.force,
+            )
+        });
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::PluginNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a plugin to install.
+├─ Path: {path:?}
+└─ Help: The path must be a directory containing exactly one '*.plugin.zsh' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::TargetExists { path }) => {
+                eprintln!(
+                    r#"Installation failed as the target file or directory already exists.
+├─ Path: {path:?}
+└─ Help: Use the '--force' option to replace it, or the '--plugins-dir' option to install elsewhere."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::InvalidConfig { path, source }) => {
+                eprintln!(
+                    r#"Installation failed due to an invalid configuration file.
+├─ Path: {path:?}
+├─ Error: {source}
+└─ Help: Correct the configuration file, see the 'Configuration' section of the README for supported keys."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error installing the plugin
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
+
+impl OnceCommand for UninstallCommand {
+    type Output = ExitCode;
+    type Error = Error;
+
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result = Config::load().and_then(|config| {
+            uninstall_plugin(
+                self.name.as_ref(),
+                &plugins_dir(self.plugins_dir, &config)?,
+                self.force,
+            )
+        });
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::TargetExists { path }) => {
+                eprintln!(
+                    r#"Uninstallation failed as the target file or directory is not an installed plugin.
+├─ Path: {path:?}
+└─ Help: Use the '--force' option to remove it anyway, or the '--plugins-dir' option to uninstall from elsewhere."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::InvalidConfig { path, source }) => {
+                eprintln!(
+                    r#"Uninstallation failed due to an invalid configuration file.
+├─ Path: {path:?}
+├─ Error: {source}
+└─ Help: Correct the configuration file, see the 'Configuration' section of the README for supported keys."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error uninstalling the plugin
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
+
+impl OnceCommand for ListCommand {
+    type Output = ExitCode;
+    type Error = Error;
+
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result = Config::load()
+            .and_then(|config| list_plugins(&plugin_roots(self.root, &config), self.json));
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::InvalidConfig { path, source }) => {
+                eprintln!(
+                    r#"Listing plugins failed due to an invalid configuration file.
+├─ Path: {path:?}
+├─ Error: {source}
+└─ Help: Correct the configuration file, see the 'Configuration' section of the README for supported keys."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error listing plugins
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
+
+impl OnceCommand for SearchCommand {
+    type Output = ExitCode;
+    type Error = Error;
+
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result = Config::load().and_then(|config| {
+            search_plugins(&plugin_roots(self.root, &config), &self.term, self.json)
+        });
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::InvalidConfig { path, source }) => {
+                eprintln!(
+                    r#"Searching plugins failed due to an invalid configuration file.
+├─ Path: {path:?}
+├─ Error: {source}
+└─ Help: Correct the configuration file, see the 'Configuration' section of the README for supported keys."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error searching plugins
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
+
+impl OnceCommand for HooksCommand {
+    type Output = ExitCode;
+    type Error = Error;
 
-    /// Do not include support for testing using the shellspec tool.
-    ///
-    /// Add testing steps to the 'Makefile' and 'shell.yml' (Github Action) files.
-    #[arg(long, short = 'S', action, conflicts_with = "template")]
-    no_shell_spec: bool,
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result = match self.cmd {
+            HooksCommands::Install(cmd) => Plugin::open(&cmd.plugin_dir)
+                .and_then(|plugin| install_hooks(&plugin, cmd.pre_push, cmd.force)),
+        };
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::PluginNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a plugin to install hooks into.
+├─ Path: {path:?}
+└─ Help: Use the '--plugin-dir' option to select a directory containing exactly one '*.plugin.zsh' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::TargetExists { path }) => {
+                eprintln!(
+                    r#"Installing hooks failed as the hook already exists.
+├─ Path: {path:?}
+└─ Help: Use the '--force' option to overwrite existing hooks."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::Git { source }) => {
+                eprintln!(
+                    r#"Installing hooks failed due to a Git error.
+├─ Error: {source}
+└─ Help: The plugin directory must be the root of a Git repository."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error installing hooks
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
 
-    /// Set the name of the Github user for inclusion in 'README.md'.
-    #[arg(long, short = 'u', env = "USER")]
-    github_user: String,
+impl OnceCommand for DepsCommand {
+    type Output = ExitCode;
+    type Error = Error;
 
-    /// Do not use the `zplugins` plugin manager for support functions.
-    ///
-    /// The zplugins manager provides a lot of features such as autoloading
-    /// functions, adding certain directories to 'path' and 'fpath' if present
-    /// and tracking global variables and aliases so they are automatically
-    /// cleaned up when a plugin is unloaded. Without this framework the
-    /// generated plugin has a lot of code to emulate some of these features.
-    #[arg(long, short = 'Z')]
-    use_plain_plugins: bool,
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result = match self.cmd {
+            DepsCommands::Add(cmd) => Plugin::open(&cmd.plugin_dir)
+                .and_then(|plugin| add_dependencies(&plugin, &cmd.names)),
+            DepsCommands::Remove(cmd) => Plugin::open(&cmd.plugin_dir)
+                .and_then(|plugin| remove_dependencies(&plugin, &cmd.names)),
+        };
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::PluginNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a plugin to modify.
+├─ Path: {path:?}
+└─ Help: Use the '--plugin-dir' option to select a directory containing exactly one '*.plugin.zsh' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::MarkerNotFound { path, marker }) => {
+                eprintln!(
+                    r#"Could not find the generated dependencies block in the plugin file.
+├─ Path: {path:?}
+└─ Help: Add the marker comments, starting with '{marker}', where dependencies are declared."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::InvalidManifest { path, source }) => {
+                eprintln!(
+                    r#"The plugin manifest could not be parsed.
+├─ Path: {path:?}
+├─ Error: {source}
+└─ Help: Correct the file, or delete it and run 'zsh-plugin manifest sync' to recreate it."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error updating the plugin dependencies
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
 
-    /// Short description of the plugin.
-    ///
-    /// This description is added to the plugin source and 'README.md' files.
-    #[arg(long, short = 'd')]
-    description: Option<String>,
+impl OnceCommand for RequiresCommand {
+    type Output = ExitCode;
+    type Error = Error;
 
-    /// The name of the new plugin.
-    ///
-    /// Plugin names are restricted to a "safe" subset corresponding to the
-    /// following regular expression `\[a-zA-Z\]\[a-zA-Z0-9_-\]``.
-    name: Name,
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result = match self.cmd {
+            RequiresCommands::Add(cmd) => {
+                let requirements = cmd
+                    .commands
+                    .into_iter()
+                    .map(|r| r.with_on_missing(cmd.on_missing))
+                    .collect::<Vec<_>>();
+                Plugin::open(&cmd.plugin_dir)
+                    .and_then(|plugin| add_requirements(&plugin, &requirements))
+            }
+            RequiresCommands::Remove(cmd) => Plugin::open(&cmd.plugin_dir)
+                .and_then(|plugin| remove_requirements(&plugin, &cmd.names)),
+        };
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::PluginNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a plugin to modify.
+├─ Path: {path:?}
+└─ Help: Use the '--plugin-dir' option to select a directory containing exactly one '*.plugin.zsh' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::MarkerNotFound { path, marker }) => {
+                eprintln!(
+                    r#"Could not find the generated requirements block in the plugin file.
+├─ Path: {path:?}
+└─ Help: Add the marker comments, starting with '{marker}', to the plugin's init function."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::InvalidManifest { path, source }) => {
+                eprintln!(
+                    r#"The plugin manifest could not be parsed.
+├─ Path: {path:?}
+├─ Error: {source}
+└─ Help: Correct the file, or delete it and run 'zsh-plugin manifest sync' to recreate it."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error updating the plugin's required commands
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ValueEnum)]
-pub(crate) enum Template {
-    /// Minimal plugin structure.
-    ///
-    /// The generated plugin contains no binary or functions directories,
-    /// no Git directory, no GitHub workflows, and no support for aliases,
-    /// shellcheck or  shellspec.
-    Minimal,
-
-    /// Simple in-line function plugin structure.
-    ///
-    /// The generated plugin contains support for aliases, shellcheck and
-    /// shellspec, but all functions are defined in-line in the main plugin.
-    /// It does not include the binary or functions directories, or
-    /// GitHub workflows.
-    Simple,
+impl OnceCommand for ManifestCommand {
+    type Output = ExitCode;
+    type Error = Error;
 
-    /// Complete plugin structure with all optional components included.
-    Complete,
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result = match self.cmd {
+            ManifestCommands::Show(cmd) => {
+                Plugin::open(&cmd.plugin_dir).and_then(|plugin| show_manifest(&plugin))
+            }
+            ManifestCommands::Sync(cmd) => {
+                Plugin::open(&cmd.plugin_dir).and_then(|plugin| sync_manifest(&plugin, cmd.check))
+            }
+        };
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::PluginNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a plugin to describe.
+├─ Path: {path:?}
+└─ Help: Use the '--plugin-dir' option to select a directory containing exactly one '*.plugin.zsh' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::InvalidManifest { path, source }) => {
+                eprintln!(
+                    r#"The plugin manifest could not be parsed.
+├─ Path: {path:?}
+├─ Error: {source}
+└─ Help: Correct the file, or delete it and run 'zsh-plugin manifest sync' to recreate it."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error maintaining the plugin manifest
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
 }
 
-// ------------------------------------------------------------------------------------------------
-// Command Implementations
-// ------------------------------------------------------------------------------------------------
+impl OnceCommand for VersionCommand {
+    type Output = ExitCode;
+    type Error = Error;
 
-impl OnceCommand for Cli {
+    fn execute(self) -> Result<Self::Output, Self::Error> {
+        let result = match self.cmd {
+            VersionCommands::Show(cmd) => {
+                Plugin::open(&cmd.plugin_dir).and_then(|plugin| show_version(&plugin))
+            }
+            VersionCommands::Bump(cmd) => Plugin::open(&cmd.plugin_dir)
+                .and_then(|plugin| bump_version(&plugin, cmd.part, cmd.from_commits, cmd.tag)),
+        };
+        match result {
+            Ok(code) => Ok(code),
+            Err(Error::PluginNotFound { path }) => {
+                eprintln!(
+                    r#"Could not find a plugin to version.
+├─ Path: {path:?}
+└─ Help: Use the '--plugin-dir' option to select a directory containing exactly one '*.plugin.zsh' file."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::InvalidVersion { value }) => {
+                eprintln!(
+                    r#"The plugin file does not contain a valid version.
+├─ Value: {value:?}
+└─ Help: Set the '@version' tag in the plugin file's header to a semantic version, e.g. '# @version 0.1.0'."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::Git { source }) => {
+                eprintln!(
+                    r#"Versioning failed due to a Git error.
+├─ Error: {source}
+└─ Help: The options '--from-commits' and '--tag' require the plugin directory to be a Git repository; '--tag' also requires a new tag name and no other staged changes."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(e) => {
+                eprintln!(
+                    r#"An error versioning the plugin
+└─ Error: {e}"#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
+
+impl OnceCommand for AddSettingCommand {
     type Output = ExitCode;
     type Error = Error;
 
     fn execute(self) -> Result<Self::Output, Self::Error> {
-        init_tracing(self.verbosity)?;
-        self.cmd.clone().execute()
+        let setting = Setting::new(
+            &self.name,
+            self.kind,
+            self.default.as_deref(),
+            self.description.as_deref(),
+        )?;
+        let plugin = Plugin::open(&self.plugin_dir)?;
+        add_setting(&plugin, &setting)
     }
 }
 
-impl OnceCommand for Commands {
+impl OnceCommand for AddModuleCommand {
     type Output = ExitCode;
     type Error = Error;
 
     fn execute(self) -> Result<Self::Output, Self::Error> {
-        match self {
-            Commands::Init(init_command) => init_command.execute(),
-        }
+        let plugin = Plugin::open(&self.plugin_dir)?;
+        add_new_module(&plugin, &self.name, self.force)
     }
 }
 
@@ -233,7 +2012,10 @@ impl OnceCommand for InitCommand {
     fn execute(mut self) -> Result<Self::Output, Self::Error> {
         let force = self.force();
         self.normalize();
-        match init_new_plugin(self.into(), force) {
+        match Config::load()
+            .and_then(|config| self.apply_defaults(&config))
+            .and_then(|()| init_new_plugin(self.into(), force))
+        {
             Ok(code) => Ok(code),
             Err(Error::GitInit { source }) => {
                 eprintln!(
@@ -243,6 +2025,23 @@ impl OnceCommand for InitCommand {
                 );
                 Ok(ExitCode::FAILURE)
             }
+            Err(Error::InvalidConfig { path, source }) => {
+                eprintln!(
+                    r#"Initialization failed due to an invalid configuration file.
+├─ Path: {path:?}
+├─ Error: {source}
+└─ Help: Correct the configuration file, see the 'Configuration' section of the README for supported keys."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
+            Err(Error::InvalidVersion { value }) => {
+                eprintln!(
+                    r#"Initialization failed due to an invalid version in the configuration file.
+├─ Value: {value:?}
+└─ Help: Set the 'version' key to a semantic version, e.g. '0.1.0'."#
+                );
+                Ok(ExitCode::FAILURE)
+            }
             Err(Error::InvalidName { kind }) => {
                 eprintln!(
                     r#"Initialization failed due to invalid plugin name.
@@ -294,8 +2093,33 @@ impl InitCommand {
     pub(crate) fn no_git_init(&self) -> bool {
         self.no_git_init
     }
-    pub(crate) fn no_github_dir(&self) -> bool {
-        self.no_github_dir
+    pub(crate) fn initial_branch(&self) -> &str {
+        &self.initial_branch
+    }
+    pub(crate) fn no_initial_commit(&self) -> bool {
+        self.no_initial_commit
+    }
+    pub(crate) fn no_git_remote(&self) -> bool {
+        self.no_git_remote
+    }
+    pub(crate) fn add_hooks(&self) -> bool {
+        self.add_hooks
+    }
+    pub(crate) fn add_pre_push_hook(&self) -> bool {
+        self.add_pre_push_hook
+    }
+    pub(crate) fn ci(&self) -> CiTarget {
+        match self.ci {
+            Some(ci) => ci,
+            None if self.no_github_dir => CiTarget::Disabled,
+            None => CiTarget::Github,
+        }
+    }
+    pub(crate) fn repository(&self) -> Option<&RemoteRepository> {
+        self.repository.as_ref()
+    }
+    pub(crate) fn homepage(&self) -> Option<&str> {
+        self.homepage.as_deref()
     }
     pub(crate) fn no_aliases(&self) -> bool {
         self.no_aliases
@@ -312,18 +2136,78 @@ impl InitCommand {
     pub(crate) fn no_shell_spec(&self) -> bool {
         self.no_shell_spec
     }
+    pub(crate) fn add_modules(&self) -> &[Name] {
+        &self.add_modules
+    }
+    pub(crate) fn depends_on(&self) -> &[Name] {
+        &self.depends_on
+    }
+    pub(crate) fn env(&self) -> &[EnvVar] {
+        &self.env
+    }
+    pub(crate) fn add_hook(&self) -> &[ShellHook] {
+        &self.add_hook
+    }
+    pub(crate) fn requires_command(&self) -> Vec<CommandRequirement> {
+        self.requires_command
+            .iter()
+            .map(|r| r.clone().with_on_missing(self.on_missing_command))
+            .collect()
+    }
     pub(crate) fn use_plain_plugins(&self) -> bool {
         self.use_plain_plugins
     }
     pub(crate) fn description(&self) -> Option<&str> {
         self.description.as_deref()
     }
-    pub(crate) fn github_user(&self) -> &str {
-        &self.github_user
-    }
     pub(crate) fn name(&self) -> &Name {
         &self.name
     }
+    pub(crate) fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+    pub(crate) fn email(&self) -> Option<&str> {
+        self.email.as_deref()
+    }
+    pub(crate) fn license(&self) -> Option<&str> {
+        self.license.as_deref()
+    }
+    pub(crate) fn version(&self) -> Version {
+        self.version.unwrap_or_else(Version::initial)
+    }
+    /// Fill unset author, email, license and version options from the
+    /// configuration file and then Git's global configuration.
+    fn apply_defaults(&mut self, config: &Config) -> Result<(), Error> {
+        let git_config = git2::Config::open_default().ok();
+        let git_value = |key: &str| {
+            git_config
+                .as_ref()
+                .and_then(|git_config| git_config.get_string(key).ok())
+                .filter(|value| !value.is_empty())
+        };
+        if self.author.is_none() {
+            self.author = config
+                .author()
+                .map(str::to_string)
+                .or_else(|| git_value("user.name"));
+        }
+        if self.email.is_none() {
+            self.email = config
+                .email()
+                .map(str::to_string)
+                .or_else(|| git_value("user.email"));
+        }
+        if self.license.is_none() {
+            self.license = config.license().map(str::to_string);
+        }
+        if self.version.is_none() {
+            self.version = Some(match config.version() {
+                Some(version) => version.parse()?,
+                None => Version::initial(),
+            });
+        }
+        Ok(())
+    }
     fn normalize(&mut self) {
         match self.template {
             Some(Template::Minimal) => {
@@ -350,7 +2234,13 @@ impl InitCommand {
                 self.no_shell_doc = false;
                 self.no_shell_spec = false;
             }
-            Some(Template::Complete) | None => {
+            // Without a template the complete template's additions are the default,
+            // while the opt-out options given, such as '--no-github-dir', are kept.
+            None => {
+                self.add_bin_dir = true;
+                self.add_bash_wrapper = true;
+            }
+            Some(Template::Complete) => {
                 self.add_bin_dir = true;
                 self.add_bash_wrapper = true;
                 self.no_aliases = false;
@@ -366,10 +2256,75 @@ impl InitCommand {
     }
 }
 
+impl AddWidgetCommand {
+    /// The widget, with its key binding if one was given.
+    fn widget(&self) -> Widget {
+        match &self.bind {
+            Some(key_sequence) => self.name.clone().with_binding(key_sequence, self.keymap),
+            None => self.name.clone(),
+        }
+    }
+}
+
+impl CiTarget {
+    /// The name used for this target in templates.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Github => "github",
+            Self::Gitlab => "gitlab",
+            Self::Forgejo => "forgejo",
+            Self::Disabled => "none",
+        }
+    }
+
+    /// The host of the public forge most commonly used with this target.
+    pub(crate) fn default_host(&self) -> &'static str {
+        match self {
+            Self::Github | Self::Disabled => "github.com",
+            Self::Gitlab => "gitlab.com",
+            Self::Forgejo => "codeberg.org",
+        }
+    }
+}
+
+impl From<ExportSourceArgs> for ReleaseSource {
+    fn from(args: ExportSourceArgs) -> Self {
+        Self {
+            url: args.url,
+            sha256: args.sha256,
+        }
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 // Private Functions
 // ------------------------------------------------------------------------------------------------
 
+/// The plugin roots given on the command line, or else by the configuration, or
+/// else the current directory.
+fn plugin_roots(option: Vec<PathBuf>, config: &Config) -> Vec<PathBuf> {
+    if !option.is_empty() {
+        option
+    } else {
+        let roots = config.plugin_roots();
+        if roots.is_empty() {
+            vec![PathBuf::from(".")]
+        } else {
+            roots
+        }
+    }
+}
+
+/// The plugins directory given on the command line, or else by the configuration.
+fn plugins_dir(option: Option<PathBuf>, config: &Config) -> Result<PathBuf, Error> {
+    option.or_else(|| config.plugins_dir()).ok_or_else(|| {
+        error!("No plugins directory given, and HOME is not set");
+        Error::Unknown {
+            message: "no plugins directory, use the '--plugins-dir' option".to_string(),
+        }
+    })
+}
+
 fn init_tracing(log_level: clap_verbosity_flag::Verbosity) -> Result<(), Error> {
     let log_level: LevelFilter = log_level.into();
     let filter = EnvFilter::from_default_env().add_directive(
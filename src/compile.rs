@@ -0,0 +1,151 @@
+//! Compilation of a plugin's sources to Zsh word code, `.zwc` files.
+//!
+//! The plugin file and each module are compiled with `zcompile` to a `.zwc` file
+//! alongside the source, which `source` uses in place of the source while it is
+//! the newer of the two. The autoload functions are compiled to a single digest,
+//! `functions.zwc`, next to the `functions` directory; a plain plugin's init
+//! function adds the digest to `fpath` in place of the directory while it is newer
+//! than every function file.
+//!
+//! Compiled files are ignored by Git, an entry for `*.zwc` is added to the plugin's
+//! `.gitignore` if it is missing, and removed by the `Makefile`'s `clean` target.
+
+use crate::{
+    error::Error,
+    plugin::Plugin,
+    templates::{P_DOT_GITIGNORE, P_FUNCTIONS_DIR},
+};
+use std::{
+    fs::{read_to_string, write},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process::{Command, ExitCode},
+};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+const ZWC_EXTENSION: &str = "zwc";
+const GITIGNORE_ENTRY: &str = "*.zwc";
+
+/// Compile the plugin file, modules and functions of `plugin` with `zcompile`.
+pub(crate) fn compile_plugin(plugin: &Plugin) -> Result<ExitCode, Error> {
+    trace!("compile_plugin => plugin: {plugin:?}");
+    let root = plugin.root();
+    let mut compiled: Vec<PathBuf> = Vec::default();
+
+    let mut sources = vec![relative(root, &plugin.plugin_file())];
+    sources.extend(plugin.module_files()?.iter().map(|m| relative(root, m)));
+    for source in sources {
+        let target = zwc_path(&source);
+        zcompile(root, &target, &[source])?;
+        print!(".");
+        compiled.push(target);
+    }
+
+    let functions = plugin.function_files()?;
+    if !functions.is_empty() {
+        let target = zwc_path(Path::new(P_FUNCTIONS_DIR));
+        let functions = functions
+            .iter()
+            .map(|f| relative(root, f))
+            .collect::<Vec<_>>();
+        zcompile(root, &target, &functions)?;
+        print!(".");
+        compiled.push(target);
+    }
+
+    ignore_compiled(root)?;
+    println!(". Done");
+
+    for path in compiled {
+        println!("Compiled: {}", path.display());
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+/// The compiled form of `source`, `source.zwc`.
+fn zwc_path(source: &Path) -> PathBuf {
+    let mut path = source.as_os_str().to_owned();
+    path.push(".");
+    path.push(ZWC_EXTENSION);
+    PathBuf::from(path)
+}
+
+fn relative(root: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(root).unwrap_or(path).to_path_buf()
+}
+
+/// Run `zcompile -U target sources…` in the directory `root`; the `-U` option stops
+/// aliases being expanded when the sources are compiled.
+fn zcompile(root: &Path, target: &Path, sources: &[PathBuf]) -> Result<(), Error> {
+    trace!("zcompile => target: {target:?}, sources: {sources:?}");
+    let failed = |message: String| {
+        error!("zcompile of {target:?} failed; message: {message}");
+        Error::Compile {
+            path: target.to_path_buf(),
+            message,
+        }
+    };
+    let output = Command::new("zsh")
+        .args(["-f", "-c", r#"zcompile -U "$@""#, "zsh"])
+        .arg(target)
+        .args(sources)
+        .current_dir(root)
+        .output()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => failed("'zsh' is not installed".to_string()),
+            _ => failed(e.to_string()),
+        })?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(failed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+/// Add `*.zwc` to the plugin's `.gitignore`, if it has one.
+fn ignore_compiled(root: &Path) -> Result<(), Error> {
+    let path = root.join(P_DOT_GITIGNORE);
+    if let Ok(mut content) = read_to_string(&path)
+        && !content.lines().any(|line| line.trim() == GITIGNORE_ENTRY)
+    {
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(GITIGNORE_ENTRY);
+        content.push('\n');
+        write(&path, content)?;
+    }
+    Ok(())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compiled_paths() {
+        assert_eq!(
+            zwc_path(Path::new("modules/core.zsh")),
+            PathBuf::from("modules/core.zsh.zwc")
+        );
+        assert_eq!(
+            zwc_path(Path::new("functions")),
+            PathBuf::from("functions.zwc")
+        );
+    }
+}
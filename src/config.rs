@@ -0,0 +1,177 @@
+//! User configuration of default values for this tool.
+//!
+//! The configuration file is read from `$XDG_CONFIG_HOME/zsh-plugin/config.toml`,
+//! or `$HOME/.config/zsh-plugin/config.toml` if `XDG_CONFIG_HOME` is not set. The
+//! file is optional, as are all of its keys.
+//!
+//! # Examples
+//!
+//! ```toml
+//! author = "Jane Doe"
+//! email = "jane@example.com"
+//! license = "MIT OR Apache-2.0"
+//! version = "0.1.0"
+//! plugins-dir = "~/.zsh/plugins"
+//! plugin-manager = "zplug"
+//! plugin-roots = ["~/src/zsh"]
+//! ```
+
+use crate::{error::Error, install::PluginManager};
+use flat_error::FlatError;
+use serde::Deserialize;
+use std::{
+    env::var_os,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// Default values, used when the corresponding command-line option is not set.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct Config {
+    /// The full name of the plugin author, overriding Git's `user.name`.
+    author: Option<String>,
+    /// The email address of the plugin author, overriding Git's `user.email`.
+    email: Option<String>,
+    /// A license expression for new plugins, e.g. `MIT`.
+    license: Option<String>,
+    /// The initial version for new plugins.
+    version: Option<String>,
+    /// The directory `install` adds plugins to.
+    plugins_dir: Option<PathBuf>,
+    /// The plugin manager `install` loads plugins with.
+    plugin_manager: Option<PluginManager>,
+    /// The directories `list` and `search` discover plugins in.
+    plugin_roots: Vec<PathBuf>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+const P_CONFIG_DIR: &str = "zsh-plugin";
+const P_CONFIG_FILE: &str = "config.toml";
+const P_DATA_DIR: &str = "zsh-plugin";
+const P_PLUGINS_DIR: &str = "plugins";
+
+impl Config {
+    /// Load the user's configuration file, or the default configuration if it
+    /// does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidConfig`] if the file exists but cannot be parsed.
+    pub(crate) fn load() -> Result<Self, Error> {
+        match Self::path() {
+            Some(path) if path.is_file() => {
+                trace!("Config::load => path: {path:?}");
+                toml::from_str(&read_to_string(&path)?).map_err(|e| {
+                    error!("Error parsing configuration file {path:?}, error: {e}");
+                    Error::InvalidConfig {
+                        path,
+                        source: FlatError::from_any(&e),
+                    }
+                })
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// The path of the user's configuration file, which may not exist.
+    pub(crate) fn path() -> Option<PathBuf> {
+        var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join(P_CONFIG_DIR).join(P_CONFIG_FILE))
+    }
+
+    pub(crate) fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub(crate) fn email(&self) -> Option<&str> {
+        self.email.as_deref()
+    }
+
+    pub(crate) fn license(&self) -> Option<&str> {
+        self.license.as_deref()
+    }
+
+    pub(crate) fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// The directory plugins are installed into, by default
+    /// `$XDG_DATA_HOME/zsh-plugin/plugins`, or `$HOME/.local/share/zsh-plugin/plugins`
+    /// if `XDG_DATA_HOME` is not set. A leading `~/` is replaced with `$HOME`.
+    pub(crate) fn plugins_dir(&self) -> Option<PathBuf> {
+        match &self.plugins_dir {
+            Some(dir) => expand_home(dir),
+            None => var_os("XDG_DATA_HOME")
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| {
+                    var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+                })
+                .map(|dir| dir.join(P_DATA_DIR).join(P_PLUGINS_DIR)),
+        }
+    }
+
+    pub(crate) fn plugin_manager(&self) -> Option<PluginManager> {
+        self.plugin_manager
+    }
+
+    /// The directories plugins are discovered in, with a leading `~/` replaced with
+    /// `$HOME`.
+    pub(crate) fn plugin_roots(&self) -> Vec<PathBuf> {
+        self.plugin_roots
+            .iter()
+            .filter_map(|root| expand_home(root))
+            .collect()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Replace a leading `~` in `path` with `$HOME`.
+fn expand_home(path: &Path) -> Option<PathBuf> {
+    match path.strip_prefix("~") {
+        Ok(rest) => var_os("HOME").map(|home| PathBuf::from(home).join(rest)),
+        Err(_) => Some(path.to_path_buf()),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        let config: Config = toml::from_str("author = \"Jane Doe\"\nlicense = \"MIT\"\n").unwrap();
+        assert_eq!(config.author(), Some("Jane Doe"));
+        assert_eq!(config.email(), None);
+        assert_eq!(config.license(), Some("MIT"));
+    }
+
+    #[test]
+    fn parse_empty_config() {
+        assert_eq!(toml::from_str::<Config>("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn reject_unknown_keys() {
+        assert!(toml::from_str::<Config>("auther = \"typo\"\n").is_err());
+    }
+}
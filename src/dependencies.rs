@@ -0,0 +1,93 @@
+//! The other plugins a plugin depends on.
+//!
+//! Dependencies are recorded in the `[dependencies]` section of the plugin's
+//! `plugin.toml` manifest, which is the only record of them that is read. From it
+//! the `dependencies` block of the plugin file is rendered; for zplugins this is
+//! a call to `@zplugins_declare_plugin_dependencies`, and for plain plugins a
+//! guard that fails to load the plugin unless each dependency's plugin hash, for
+//! example `SHLOG` for the plugin `shlog`, is defined.
+
+use crate::{
+    blocks::{B_DEPENDENCIES, dependencies_block, update_block},
+    error::Error,
+    manifest::Manifest,
+    name::Name,
+    plugin::Plugin,
+    readme::update_readme,
+};
+use std::process::ExitCode;
+use tracing::trace;
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Add `names` to the plugins that `plugin` depends on, ignoring any already present.
+pub(crate) fn add_dependencies(plugin: &Plugin, names: &[Name]) -> Result<ExitCode, Error> {
+    trace!("add_dependencies => plugin: {plugin:?}, names: {names:?}");
+    let mut manifest = Manifest::load_or_detect(plugin)?;
+    for name in names {
+        if name.as_ref() == plugin.name() || name.as_ref() == plugin.display_name() {
+            eprintln!("A plugin cannot depend on itself, ignoring '{name}'.");
+        } else if !manifest
+            .dependencies
+            .plugins
+            .iter()
+            .any(|d| d == name.as_ref())
+        {
+            manifest.dependencies.plugins.push(name.to_string());
+        }
+    }
+    write_dependencies(plugin, &manifest)
+}
+
+/// Remove `names` from the plugins that `plugin` depends on.
+pub(crate) fn remove_dependencies(plugin: &Plugin, names: &[Name]) -> Result<ExitCode, Error> {
+    trace!("remove_dependencies => plugin: {plugin:?}, names: {names:?}");
+    let mut manifest = Manifest::load_or_detect(plugin)?;
+    for name in names {
+        if !manifest
+            .dependencies
+            .plugins
+            .iter()
+            .any(|d| d == name.as_ref())
+        {
+            eprintln!("The plugin does not depend on '{name}', ignoring.");
+        }
+    }
+    manifest
+        .dependencies
+        .plugins
+        .retain(|d| !names.iter().any(|name| name.as_ref() == d));
+    write_dependencies(plugin, &manifest)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn write_dependencies(plugin: &Plugin, manifest: &Manifest) -> Result<ExitCode, Error> {
+    let dependencies = &manifest.dependencies.plugins;
+    update_block(
+        &plugin.plugin_file(),
+        B_DEPENDENCIES,
+        &dependencies_block(
+            plugin.name(),
+            plugin.display_name(),
+            plugin.uses_zplugins(),
+            dependencies,
+        ),
+    )?;
+    print!(".");
+    manifest.save(plugin)?;
+    update_readme(plugin)?;
+    println!(" Done");
+
+    if dependencies.is_empty() {
+        println!("No dependencies.");
+    } else {
+        println!("Depends on: {}", dependencies.join(", "));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
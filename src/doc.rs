@@ -0,0 +1,861 @@
+//! Documentation extraction and generation for existing plugins.
+//!
+//! This module parses the [shdoc](https://github.com/reconquest/shdoc) style annotations
+//! included in generated plugin files, modules and autoloaded functions, and renders
+//! them as Markdown. The following annotations are recognized.
+//!
+//! | Annotation     | Applies to      | Content                                       |
+//! | -------------- | --------------- | --------------------------------------------- |
+//! | `@name`        | file            | The name of the plugin or module.             |
+//! | `@brief`       | file            | A single line description.                    |
+//! | `@description` | file, section, function | Multi-line description, Markdown.     |
+//! | `@section`     | section         | Starts a new section of functions.            |
+//! | `@arg`         | function        | An argument, e.g. `@arg $1 string The value`. |
+//! | `@noargs`      | function        | The function takes no arguments.              |
+//! | `@example`     | function        | Multi-line example, rendered as code.         |
+//! | `@stdout`      | function        | Description of the function's output.         |
+//! | `@exitcode`    | function        | An exit code, e.g. `@exitcode 0 On success`.  |
+//! | `@see`         | function        | A reference to another function or URL.       |
+//! | `@internal`    | function        | Exclude the function from documentation.      |
+//!
+//! Any other annotation in the file header, such as `@version` or `@license`, is
+//! retained as file metadata.
+//!
+//! Aliases defined using the plugin's `define_alias` support function, or
+//! `@zplugins_define_alias`, are also recorded along with any preceding comment.
+//!
+//! Documentation may be rendered as Markdown, or as man pages using the [`man`]
+//! module.
+
+// ------------------------------------------------------------------------------------------------
+// Modules
+// ------------------------------------------------------------------------------------------------
+
+mod man;
+
+// ------------------------------------------------------------------------------------------------
+// Imports
+// ------------------------------------------------------------------------------------------------
+
+use crate::{cli::DocFormat, error::Error, plugin::Plugin, templates::P_DOC_DIR};
+use std::{
+    fmt::Write,
+    fs::{create_dir_all, read_to_string, write},
+    path::Path,
+    process::ExitCode,
+};
+use tracing::trace;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// The documentation extracted from a single source file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SourceDoc {
+    name: Option<String>,
+    brief: Option<String>,
+    description: Option<String>,
+    metadata: Vec<(String, String)>,
+    sections: Vec<SectionDoc>,
+    aliases: Vec<AliasDoc>,
+}
+
+/// All the documentation for a plugin; its main file, modules and autoloaded functions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct PluginDocs {
+    display_name: String,
+    plugin: SourceDoc,
+    modules: Vec<(String, SourceDoc)>,
+    autoloaded: Vec<FunctionDoc>,
+}
+
+/// A named group of functions within a source file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SectionDoc {
+    name: Option<String>,
+    description: Option<String>,
+    functions: Vec<FunctionDoc>,
+}
+
+/// The documentation for a single function.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct FunctionDoc {
+    name: String,
+    documented: bool,
+    internal: bool,
+    description: Option<String>,
+    args: Vec<(String, String)>,
+    noargs: bool,
+    examples: Vec<String>,
+    stdout: Option<String>,
+    exit_codes: Vec<(String, String)>,
+    see: Vec<String>,
+}
+
+/// A tracked alias and its expansion.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct AliasDoc {
+    name: String,
+    value: String,
+    description: Option<String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+const P_FUNCTIONS_DOC: &str = "functions.md";
+const P_INDEX_DOC: &str = "index.md";
+
+/// Generate documentation for `plugin` in the requested `format`.
+///
+/// For Markdown the main plugin file is documented in `index.md`, each module in
+/// `MODULE.md`, and all autoloaded functions together in `functions.md`, all within
+/// `output_dir` which defaults to the plugin's `doc` directory.
+///
+/// For man pages a page is written for the plugin and each public function into
+/// `man/man1`, along with plain text pages for `run-help` in `help`, all within
+/// `output_dir` which defaults to the plugin's root directory.
+pub(crate) fn generate_docs(
+    plugin: &Plugin,
+    format: DocFormat,
+    output_dir: Option<&Path>,
+) -> Result<ExitCode, Error> {
+    trace!("generate_docs => plugin: {plugin:?}, format: {format:?}, output_dir: {output_dir:?}");
+    let docs = PluginDocs::load(plugin)?;
+
+    match format {
+        DocFormat::Markdown => {
+            let output_dir = output_dir
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| plugin.root().join(P_DOC_DIR));
+            write_markdown(&docs, &output_dir)?;
+        }
+        DocFormat::Man => {
+            let output_dir = output_dir.unwrap_or(plugin.root());
+            man::write_man_pages(&docs, output_dir)?;
+        }
+    }
+
+    println!(" Done");
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Parse the annotations in a plugin or module source file.
+pub(crate) fn parse_source(source: &str) -> SourceDoc {
+    let mut doc = SourceDoc::default();
+    let mut block: Vec<String> = Vec::default();
+
+    for line in source.lines() {
+        let line = line.trim_start();
+        if let Some(text) = comment_text(line) {
+            if let Some(text) = text {
+                block.push(text);
+            }
+            continue;
+        }
+        let tags = parse_tags(&block);
+        let comment = block.join("\n");
+        block.clear();
+
+        if let Some(name) = function_name(line) {
+            let function = FunctionDoc::from_tags(name, &tags);
+            if doc.sections.is_empty() {
+                doc.sections.push(SectionDoc::default());
+            }
+            if let Some(section) = doc.sections.last_mut() {
+                section.functions.push(function);
+            }
+        } else if let Some((name, value)) = alias_definition(line) {
+            doc.aliases.push(AliasDoc {
+                name,
+                value,
+                description: tag_value(&tags, T_DESCRIPTION).or_else(|| non_empty(&comment)),
+            });
+        } else if let Some((_, name)) = tags.iter().find(|(tag, _)| tag == T_SECTION) {
+            doc.sections.push(SectionDoc {
+                name: non_empty(name),
+                description: tag_value(&tags, T_DESCRIPTION),
+                functions: Vec::default(),
+            });
+        } else if tags.iter().any(|(tag, _)| tag == T_NAME || tag == T_BRIEF) {
+            for (tag, value) in tags {
+                match tag.as_str() {
+                    T_NAME => doc.name = non_empty(&value),
+                    T_BRIEF => doc.brief = non_empty(&value),
+                    T_DESCRIPTION => doc.description = non_empty(&value),
+                    _ => doc.metadata.push((tag, value.trim().to_string())),
+                }
+            }
+        }
+    }
+
+    doc
+}
+
+/// Parse the annotations in an autoloaded function file, where the file `name`
+/// is the function name.
+pub(crate) fn parse_autoload(name: &str, source: &str) -> FunctionDoc {
+    let block = source
+        .lines()
+        .map(str::trim_start)
+        .take_while(|line| line.is_empty() || line.starts_with('#'))
+        .filter_map(|line| comment_text(line).flatten())
+        .collect::<Vec<_>>();
+    FunctionDoc::from_tags(name.to_string(), &parse_tags(&block))
+}
+
+/// The lines of the body of the function `name` in a plugin or module source
+/// file, without its braces, if `source` defines it.
+pub(crate) fn function_body(source: &str, name: &str) -> Option<Vec<String>> {
+    let mut lines = source.lines();
+    let definition =
+        lines.find(|line| function_name(line.trim_start()).is_some_and(|n| n == name))?;
+    let indent = definition.len() - definition.trim_start().len();
+    if let Some((_, rest)) = definition.split_once('{')
+        && let Some(inline) = rest.trim().strip_suffix('}')
+    {
+        return Some(vec![inline.trim().to_string()]);
+    }
+    Some(
+        lines
+            .take_while(|line| line.trim() != "}" || line.len() - line.trim_start().len() != indent)
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl PluginDocs {
+    /// Parse the plugin file, all modules and all autoloaded functions of `plugin`.
+    pub(crate) fn load(plugin: &Plugin) -> Result<Self, Error> {
+        let mut docs = Self {
+            display_name: plugin.display_name().to_string(),
+            plugin: parse_source(&read_to_string(plugin.plugin_file())?),
+            ..Default::default()
+        };
+        for module in plugin.module_files()? {
+            let name = file_name(&module.with_extension(""));
+            docs.modules
+                .push((name, parse_source(&read_to_string(&module)?)));
+        }
+        for file in plugin.function_files()? {
+            docs.autoloaded
+                .push(parse_autoload(&file_name(&file), &read_to_string(&file)?));
+        }
+        Ok(docs)
+    }
+
+    /// The plugin name as declared by the `@name` tag in the plugin file.
+    pub(crate) fn display_name(&self) -> &str {
+        &self.display_name
+    }
+
+    /// The documentation of the main plugin file.
+    pub(crate) fn plugin(&self) -> &SourceDoc {
+        &self.plugin
+    }
+
+    /// The documentation of each module, by module name.
+    pub(crate) fn modules(&self) -> impl Iterator<Item = (&str, &SourceDoc)> {
+        self.modules.iter().map(|(name, doc)| (name.as_str(), doc))
+    }
+
+    /// The documentation of all autoloaded functions.
+    pub(crate) fn autoloaded(&self) -> impl Iterator<Item = &FunctionDoc> {
+        self.autoloaded.iter()
+    }
+
+    /// All functions in the plugin file, modules and autoloaded functions.
+    pub(crate) fn functions(&self) -> impl Iterator<Item = &FunctionDoc> {
+        self.plugin
+            .functions()
+            .chain(self.modules.iter().flat_map(|(_, doc)| doc.functions()))
+            .chain(self.autoloaded())
+    }
+
+    /// All tracked aliases in the plugin file and modules.
+    pub(crate) fn aliases(&self) -> impl Iterator<Item = &AliasDoc> {
+        self.plugin
+            .aliases()
+            .chain(self.modules.iter().flat_map(|(_, doc)| doc.aliases()))
+    }
+
+    /// The autoloaded functions presented as a single source document.
+    fn autoloaded_doc(&self) -> SourceDoc {
+        SourceDoc {
+            name: Some(format!("{} functions", self.display_name())),
+            brief: Some("Functions autoloaded from the plugin's `functions` directory.".into()),
+            sections: vec![SectionDoc {
+                functions: self.autoloaded.clone(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+}
+
+impl SourceDoc {
+    pub(crate) fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub(crate) fn brief(&self) -> Option<&str> {
+        self.brief.as_deref()
+    }
+
+    pub(crate) fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// The values of all file-level `@tag` annotations named `tag`.
+    pub(crate) fn metadata<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a str> {
+        self.metadata
+            .iter()
+            .filter(move |(t, _)| t.strip_prefix('@') == Some(tag))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub(crate) fn sections(&self) -> impl Iterator<Item = &SectionDoc> {
+        self.sections.iter()
+    }
+
+    pub(crate) fn functions(&self) -> impl Iterator<Item = &FunctionDoc> {
+        self.sections.iter().flat_map(|s| s.functions.iter())
+    }
+
+    pub(crate) fn aliases(&self) -> impl Iterator<Item = &AliasDoc> {
+        self.aliases.iter()
+    }
+
+    /// Render the documentation as a Markdown document.
+    pub(crate) fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        let _ = writeln!(md, "# {}\n", self.name().unwrap_or("Plugin"));
+        if let Some(brief) = self.brief() {
+            let _ = writeln!(md, "{brief}\n");
+        }
+        for (tag, value) in &self.metadata {
+            let _ = writeln!(md, "* **{}**: {value}", &tag[1..]);
+        }
+        if !self.metadata.is_empty() {
+            md.push('\n');
+        }
+        if let Some(description) = self.description() {
+            let _ = writeln!(md, "## Overview\n\n{description}\n");
+        }
+
+        let documented = self
+            .functions()
+            .filter(|f| f.is_documented())
+            .collect::<Vec<_>>();
+        if !documented.is_empty() {
+            let _ = writeln!(md, "## Index\n");
+            for function in &documented {
+                let _ = writeln!(
+                    md,
+                    "* [{0}](#{1})",
+                    function.name(),
+                    anchor(function.name())
+                );
+            }
+            md.push('\n');
+        }
+
+        for section in self.sections() {
+            let functions = section
+                .functions()
+                .filter(|f| f.is_documented())
+                .collect::<Vec<_>>();
+            if let Some(name) = section.name() {
+                if functions.is_empty() && section.description.is_none() {
+                    continue;
+                }
+                let _ = writeln!(md, "## {name}\n");
+                if let Some(description) = &section.description {
+                    let _ = writeln!(md, "{description}\n");
+                }
+            }
+            for function in functions {
+                function.write_markdown(&mut md);
+            }
+        }
+
+        md.truncate(md.trim_end().len());
+        md.push('\n');
+        md
+    }
+}
+
+impl SectionDoc {
+    pub(crate) fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub(crate) fn functions(&self) -> impl Iterator<Item = &FunctionDoc> {
+        self.functions.iter()
+    }
+}
+
+impl FunctionDoc {
+    fn from_tags(name: String, tags: &[(String, String)]) -> Self {
+        let mut function = Self {
+            internal: name.starts_with('.') || name.starts_with('@'),
+            documented: !tags.is_empty(),
+            name,
+            ..Default::default()
+        };
+        for (tag, value) in tags {
+            match tag.as_str() {
+                T_DESCRIPTION => function.description = non_empty(value),
+                T_ARG => function.args.push(split_first_word(value)),
+                T_NOARGS => function.noargs = true,
+                T_EXAMPLE => function.examples.push(value.to_string()),
+                T_STDOUT => function.stdout = non_empty(value),
+                T_EXITCODE => function.exit_codes.push(split_first_word(value)),
+                T_SEE => function.see.push(value.trim().to_string()),
+                T_INTERNAL => function.internal = true,
+                _ => {}
+            }
+        }
+        function
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Returns `true` if the function should be publicly documented, that is it
+    /// has a documentation comment and is not internal.
+    pub(crate) fn is_documented(&self) -> bool {
+        self.documented && !self.internal
+    }
+
+    /// Returns `true` if the function is part of the plugin's public interface,
+    /// that is it is neither internal nor a plugin lifecycle function.
+    pub(crate) fn is_public(&self) -> bool {
+        !self.internal
+            && !self.name.ends_with(LIFECYCLE_INIT_SUFFIX)
+            && !self.name.ends_with(LIFECYCLE_UNLOAD_SUFFIX)
+    }
+
+    fn write_markdown(&self, md: &mut String) {
+        let _ = writeln!(md, "### {}\n", self.name());
+        if let Some(description) = self.description() {
+            let _ = writeln!(md, "{description}\n");
+        }
+        for example in &self.examples {
+            let _ = writeln!(md, "#### Example\n\n```bash\n{example}\n```\n");
+        }
+        if !self.args.is_empty() {
+            let _ = writeln!(md, "#### Arguments\n");
+            for (arg, description) in &self.args {
+                let _ = writeln!(md, "* **{arg}** {description}");
+            }
+            md.push('\n');
+        } else if self.noargs {
+            let _ = writeln!(md, "_Function has no arguments._\n");
+        }
+        if let Some(stdout) = &self.stdout {
+            let _ = writeln!(md, "#### Output on stdout\n\n* {stdout}\n");
+        }
+        if !self.exit_codes.is_empty() {
+            let _ = writeln!(md, "#### Exit codes\n");
+            for (code, description) in &self.exit_codes {
+                let _ = writeln!(md, "* **{code}**: {description}");
+            }
+            md.push('\n');
+        }
+        if !self.see.is_empty() {
+            let _ = writeln!(md, "#### See also\n");
+            for see in &self.see {
+                let _ = writeln!(md, "* {see}");
+            }
+            md.push('\n');
+        }
+    }
+}
+
+impl AliasDoc {
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub(crate) fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn write_markdown(docs: &PluginDocs, output_dir: &Path) -> Result<(), Error> {
+    create_dir_all(output_dir)?;
+
+    write(output_dir.join(P_INDEX_DOC), docs.plugin().to_markdown())?;
+    print!(".");
+
+    for (name, module_doc) in docs.modules() {
+        write(
+            output_dir.join(format!("{name}.md")),
+            module_doc.to_markdown(),
+        )?;
+        print!(".");
+    }
+
+    if docs.autoloaded().next().is_some() {
+        write(
+            output_dir.join(P_FUNCTIONS_DOC),
+            docs.autoloaded_doc().to_markdown(),
+        )?;
+        print!(".");
+    }
+
+    Ok(())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+const T_ARG: &str = "@arg";
+const T_BRIEF: &str = "@brief";
+const T_DESCRIPTION: &str = "@description";
+const T_EXAMPLE: &str = "@example";
+const T_EXITCODE: &str = "@exitcode";
+const T_INTERNAL: &str = "@internal";
+const T_NAME: &str = "@name";
+const T_NOARGS: &str = "@noargs";
+const T_SECTION: &str = "@section";
+const T_SEE: &str = "@see";
+const T_STDOUT: &str = "@stdout";
+
+const MULTI_LINE_TAGS: &[&str] = &[T_DESCRIPTION, T_EXAMPLE];
+
+const DEFINE_ALIAS_SUFFIX: &str = "_define_alias";
+const ZPLUGINS_DEFINE_ALIAS: &str = "@zplugins_define_alias";
+
+const LIFECYCLE_INIT_SUFFIX: &str = "_plugin_init";
+const LIFECYCLE_UNLOAD_SUFFIX: &str = "_plugin_unload";
+
+/// Returns `None` if `line` is not a comment, `Some(None)` if it is a comment
+/// with no documentation content, else the text of the comment.
+fn comment_text(line: &str) -> Option<Option<String>> {
+    let text = line.strip_prefix('#')?;
+    let text = text.strip_prefix(' ').unwrap_or(text);
+    if text.starts_with('!')
+        || text.contains("-*-")
+        || text.starts_with("shellcheck ")
+        || (text.len() >= 3 && text.chars().all(|c| c == '#'))
+    {
+        Some(None)
+    } else {
+        Some(Some(text.trim_end().to_string()))
+    }
+}
+
+/// Split a comment block into `(tag, value)` pairs, multi-line tags collect all
+/// following lines up to the next tag.
+fn parse_tags(block: &[String]) -> Vec<(String, String)> {
+    let mut tags: Vec<(String, Vec<String>)> = Vec::default();
+    for line in block {
+        let word = line.split_whitespace().next().unwrap_or_default();
+        if word.starts_with('@')
+            && word.len() > 1
+            && word[1..].chars().all(|c| c.is_ascii_alphabetic())
+        {
+            let rest = line[word.len()..].trim();
+            tags.push((word.to_string(), vec![rest.to_string()]));
+        } else if let Some((tag, lines)) = tags.last_mut()
+            && MULTI_LINE_TAGS.contains(&tag.as_str())
+        {
+            lines.push(line.to_string());
+        }
+    }
+    tags.into_iter()
+        .map(|(tag, lines)| {
+            let value = lines.join("\n");
+            let value = if tag == T_EXAMPLE {
+                value.trim_matches('\n').to_string()
+            } else {
+                value.trim().to_string()
+            };
+            (tag, value)
+        })
+        .collect()
+}
+
+/// Returns the function name if `line` is a function definition.
+fn function_name(line: &str) -> Option<String> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || "_.-:@".contains(c);
+    if let Some(rest) = line.strip_prefix("function ") {
+        let name = rest
+            .trim_start()
+            .split(|c: char| c.is_whitespace() || c == '(')
+            .next()?;
+        (!name.is_empty() && name.chars().all(is_name_char)).then(|| name.to_string())
+    } else {
+        let (name, rest) = line.split_once("()")?;
+        let name = name.trim_end();
+        let rest = rest.trim_start();
+        (!name.is_empty()
+            && name.chars().all(is_name_char)
+            && (rest.is_empty() || rest.starts_with('{')))
+        .then(|| name.to_string())
+    }
+}
+
+/// Returns the alias name and value if `line` calls a `define_alias` function.
+fn alias_definition(line: &str) -> Option<(String, String)> {
+    let mut words = shell_words(line).into_iter();
+    let command = words.next()?;
+    if command == ZPLUGINS_DEFINE_ALIAS {
+        let _plugin = words.next()?;
+    } else if !(command.starts_with('.') && command.ends_with(DEFINE_ALIAS_SUFFIX)) {
+        return None;
+    }
+    Some((words.next()?, words.next().unwrap_or_default()))
+}
+
+/// Split a line into words, removing single and double quotes.
+pub(crate) fn shell_words(line: &str) -> Vec<String> {
+    let mut words = Vec::default();
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.get_or_insert_default().push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                word.get_or_insert_default();
+            }
+            (None, '#') if word.is_none() => break,
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_default().push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+fn tag_value(tags: &[(String, String)], tag: &str) -> Option<String> {
+    tags.iter()
+        .find(|(t, _)| t == tag)
+        .and_then(|(_, v)| non_empty(v))
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn split_first_word(value: &str) -> (String, String) {
+    let value = value.trim();
+    match value.split_once(char::is_whitespace) {
+        Some((first, rest)) => (first.to_string(), rest.trim().to_string()),
+        None => (value.to_string(), String::new()),
+    }
+}
+
+fn anchor(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"# -*- mode: sh; eval: (sh-set-shell "zsh") -*-
+#
+# @name my-plugin
+# @brief Does things.
+# @version 0.1.0
+#
+# @description
+#
+# Long description.
+#
+# More description.
+#
+
+############################################################################
+# @section public
+# @description Public functions.
+#
+
+#
+# @description Say hello.
+#
+# @arg $1 string The name to greet.
+# @exitcode 0 Always.
+#
+my_plugin_hello() {
+    # Not a doc comment.
+    printf "Hello %s" "${1}"
+}
+
+#
+# @description Internal helper.
+#
+.my_plugin_helper() {
+    :
+}
+
+my_plugin_undocumented() {
+    :
+}
+
+# Alias hi says hello.
+.my_plugin_define_alias hi 'my_plugin_hello world'
+"#;
+
+    #[test]
+    fn parse_file_header() {
+        let doc = parse_source(SOURCE);
+        assert_eq!(doc.name(), Some("my-plugin"));
+        assert_eq!(doc.brief(), Some("Does things."));
+        assert_eq!(
+            doc.description(),
+            Some("Long description.\n\nMore description.")
+        );
+        assert_eq!(
+            doc.metadata,
+            vec![("@version".to_string(), "0.1.0".to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_sections_and_functions() {
+        let doc = parse_source(SOURCE);
+        let sections = doc.sections().collect::<Vec<_>>();
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].name(), Some("public"));
+        let names = doc.functions().map(|f| f.name()).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "my_plugin_hello",
+                ".my_plugin_helper",
+                "my_plugin_undocumented"
+            ]
+        );
+        let documented = doc
+            .functions()
+            .filter(|f| f.is_documented())
+            .map(|f| f.name())
+            .collect::<Vec<_>>();
+        assert_eq!(documented, vec!["my_plugin_hello"]);
+    }
+
+    #[test]
+    fn parse_function_tags() {
+        let doc = parse_source(SOURCE);
+        let hello = doc.functions().next().unwrap();
+        assert_eq!(hello.description(), Some("Say hello."));
+        assert_eq!(
+            hello.args,
+            vec![("$1".to_string(), "string The name to greet.".to_string())]
+        );
+        assert_eq!(
+            hello.exit_codes,
+            vec![("0".to_string(), "Always.".to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_autoload_function() {
+        let source = "# shellcheck shell=bash\n#\n# @description Example.\n#\n# @noargs\n#\n\nbuiltin emulate -L zsh\n# @description Not this.\n";
+        let function = parse_autoload("my_example", source);
+        assert_eq!(function.name(), "my_example");
+        assert_eq!(function.description(), Some("Example."));
+        assert!(function.noargs);
+        assert!(function.is_documented());
+    }
+
+    #[test]
+    fn parse_aliases() {
+        let doc = parse_source(SOURCE);
+        let aliases = doc.aliases().collect::<Vec<_>>();
+        assert_eq!(aliases.len(), 1);
+        assert_eq!(aliases[0].name(), "hi");
+        assert_eq!(aliases[0].value(), "my_plugin_hello world");
+        assert_eq!(aliases[0].description(), Some("Alias hi says hello."));
+        assert_eq!(
+            alias_definition("@zplugins_define_alias my_plugin ll 'ls -l'"),
+            Some(("ll".to_string(), "ls -l".to_string()))
+        );
+        assert_eq!(alias_definition("alias ll='ls -l'"), None);
+    }
+
+    #[test]
+    fn split_shell_words() {
+        assert_eq!(
+            shell_words(r#"cmd 'a b' "c d"e f # comment"#),
+            vec!["cmd", "a b", "c de", "f"]
+        );
+        assert_eq!(shell_words("cmd ''"), vec!["cmd", ""]);
+    }
+
+    #[test]
+    fn function_definitions() {
+        assert_eq!(function_name("foo() {"), Some("foo".to_string()));
+        assert_eq!(function_name("foo () {"), Some("foo".to_string()));
+        assert_eq!(function_name("function foo {"), Some("foo".to_string()));
+        assert_eq!(function_name("function foo() {"), Some("foo".to_string()));
+        assert_eq!(function_name(".foo_bar() {"), Some(".foo_bar".to_string()));
+        assert_eq!(function_name("foo"), None);
+        assert_eq!(function_name("echo \"$(foo)\""), None);
+    }
+
+    #[test]
+    fn function_bodies() {
+        assert_eq!(
+            function_body(SOURCE, "my_plugin_hello"),
+            Some(vec![
+                "    # Not a doc comment.".to_string(),
+                "    printf \"Hello %s\" \"${1}\"".to_string(),
+            ])
+        );
+        assert_eq!(
+            function_body("f() { command ls \"$@\"; }\n", "f"),
+            Some(vec!["command ls \"$@\";".to_string()])
+        );
+        assert_eq!(function_body(SOURCE, "my_plugin_missing"), None);
+    }
+
+    #[test]
+    fn render_markdown() {
+        let md = parse_source(SOURCE).to_markdown();
+        assert!(md.starts_with("# my-plugin\n\nDoes things.\n"));
+        assert!(md.contains("* **version**: 0.1.0"));
+        assert!(md.contains("## public\n\nPublic functions.\n"));
+        assert!(md.contains("### my_plugin_hello\n\nSay hello.\n"));
+        assert!(!md.contains("my_plugin_helper"));
+        assert!(!md.contains("my_plugin_undocumented"));
+    }
+}
@@ -0,0 +1,319 @@
+//! Rendering of plugin documentation as man pages and `run-help` pages.
+//!
+//! A section 1 man page is generated for the plugin itself, listing all public
+//! functions, and one for each public, documented, function. The plugin's init
+//! function adds the `man` directory to `MANPATH` so that `man PLUGIN` and
+//! `man FUNCTION` work once the plugin is loaded.
+//!
+//! Plain text pages are also generated into the `help` directory using the format
+//! of the Zsh help files read by `run-help` from `HELPDIR`. The plugin's init
+//! function points `HELPDIR` at this directory, and unload restores it, so that
+//! `run-help FUNCTION` shows the function's page rather than its source.
+
+use super::{FunctionDoc, PluginDocs};
+use crate::error::Error;
+use std::{
+    fmt::Write,
+    fs::{create_dir_all, write},
+    path::Path,
+};
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+const P_HELP_DIR: &str = "help";
+const P_MAN_DIR: &str = "man";
+const P_MAN1_DIR: &str = "man1";
+
+const MANUAL_TITLE: &str = "Zsh Plugin Manual";
+
+/// Write man pages into `output_dir/man/man1` and help pages into `output_dir/help`.
+pub(super) fn write_man_pages(docs: &PluginDocs, output_dir: &Path) -> Result<(), Error> {
+    let man_dir = output_dir.join(P_MAN_DIR).join(P_MAN1_DIR);
+    create_dir_all(&man_dir)?;
+    let help_dir = output_dir.join(P_HELP_DIR);
+    create_dir_all(&help_dir)?;
+
+    let functions = docs
+        .functions()
+        .filter(|f| f.is_documented() && f.is_public())
+        .collect::<Vec<_>>();
+
+    write(
+        man_dir.join(format!("{}.1", docs.display_name())),
+        plugin_page(docs, &functions),
+    )?;
+    print!(".");
+
+    for function in functions {
+        write(
+            man_dir.join(format!("{}.1", function.name())),
+            function_page(docs, function),
+        )?;
+        write(help_dir.join(function.name()), help_page(function))?;
+        print!(".");
+    }
+
+    Ok(())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn plugin_page(docs: &PluginDocs, functions: &[&FunctionDoc]) -> String {
+    let plugin = docs.plugin();
+    let mut page = title(docs.display_name(), docs.display_name());
+
+    let _ = writeln!(
+        page,
+        ".SH NAME\n{} \\- {}",
+        escape(docs.display_name()),
+        escape(plugin.brief().unwrap_or("Zsh plugin"))
+    );
+    if let Some(description) = plugin.description() {
+        let _ = writeln!(page, ".SH DESCRIPTION\n{}", to_roff(description));
+    }
+    if !functions.is_empty() {
+        let _ = writeln!(page, ".SH FUNCTIONS");
+        for function in functions {
+            let _ = writeln!(
+                page,
+                ".TP\n.B {}\n{}",
+                escape(function.name()),
+                escape(&inline(&summary(function)))
+            );
+        }
+        let _ = writeln!(page, ".SH SEE ALSO");
+        let see_also = functions
+            .iter()
+            .map(|f| format!(".BR {} (1)", escape(f.name())))
+            .collect::<Vec<_>>();
+        let _ = writeln!(page, "{}", see_also.join(" ,\n"));
+    }
+
+    page
+}
+
+fn function_page(docs: &PluginDocs, function: &FunctionDoc) -> String {
+    let mut page = title(function.name(), docs.display_name());
+
+    let _ = writeln!(
+        page,
+        ".SH NAME\n{} \\- {}",
+        escape(function.name()),
+        escape(&inline(&summary(function)))
+    );
+    let _ = writeln!(
+        page,
+        ".SH SYNOPSIS\n.B {}{}",
+        escape(function.name()),
+        function
+            .args
+            .iter()
+            .map(|(arg, _)| format!(" \\fI{}\\fR", escape(arg)))
+            .collect::<String>()
+    );
+    if let Some(description) = function.description() {
+        let _ = writeln!(page, ".SH DESCRIPTION\n{}", to_roff(description));
+    }
+    if !function.args.is_empty() {
+        let _ = writeln!(page, ".SH ARGUMENTS");
+        for (arg, description) in &function.args {
+            let _ = writeln!(
+                page,
+                ".TP\n.I {}\n{}",
+                escape(arg),
+                escape(&inline(description))
+            );
+        }
+    } else if function.noargs {
+        let _ = writeln!(page, ".SH ARGUMENTS\nThis function takes no arguments.");
+    }
+    if let Some(stdout) = &function.stdout {
+        let _ = writeln!(page, ".SH OUTPUT\n{}", escape(stdout));
+    }
+    if !function.exit_codes.is_empty() {
+        let _ = writeln!(page, ".SH EXIT STATUS");
+        for (code, description) in &function.exit_codes {
+            let _ = writeln!(page, ".TP\n.B {}\n{}", escape(code), escape(description));
+        }
+    }
+    if !function.examples.is_empty() {
+        let _ = writeln!(page, ".SH EXAMPLES");
+        for example in &function.examples {
+            let _ = writeln!(page, ".PP\n.nf\n.RS 4\n{}\n.RE\n.fi", escape_lines(example));
+        }
+    }
+    let _ = writeln!(
+        page,
+        ".SH SEE ALSO\n.BR {} (1)",
+        escape(docs.display_name())
+    );
+    for see in &function.see {
+        let _ = writeln!(page, ".br\n{}", escape(see));
+    }
+
+    page
+}
+
+fn help_page(function: &FunctionDoc) -> String {
+    let indent = |text: &str| {
+        text.lines()
+            .map(|line| {
+                if line.is_empty() {
+                    String::new()
+                } else {
+                    format!("       {line}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let mut page = function.name().to_string();
+    for (arg, _) in &function.args {
+        let _ = write!(page, " {arg}");
+    }
+    page.push('\n');
+    if let Some(description) = function.description() {
+        let _ = writeln!(page, "{}", indent(description));
+    }
+    if !function.args.is_empty() {
+        page.push('\n');
+        for (arg, description) in &function.args {
+            let _ = writeln!(page, "{}", indent(&format!("{arg}  {description}")));
+        }
+    }
+    for example in &function.examples {
+        let _ = writeln!(page, "\n{}", indent(example));
+    }
+
+    page
+}
+
+fn title(name: &str, source: &str) -> String {
+    format!(
+        ".TH \"{}\" \"1\" \"\" \"{}\" \"{MANUAL_TITLE}\"\n",
+        escape(&name.to_ascii_uppercase()).replace('"', "\\(dq"),
+        escape(source).replace('"', "\\(dq"),
+    )
+}
+
+/// The first sentence of the function's description.
+fn summary(function: &FunctionDoc) -> String {
+    let description = function.description().unwrap_or_default();
+    let first_line = description.lines().next().unwrap_or_default();
+    match first_line.find(". ") {
+        Some(end) => first_line[..=end].to_string(),
+        None => first_line.to_string(),
+    }
+}
+
+/// Convert the simple Markdown used in descriptions into roff requests.
+fn to_roff(markdown: &str) -> String {
+    let mut lines: Vec<String> = Vec::default();
+    for line in markdown.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            if lines.last().is_some_and(|l| l != ".PP") {
+                lines.push(".PP".to_string());
+            }
+        } else if let Some(heading) = line.strip_prefix('#') {
+            lines.push(format!(
+                ".SS {}",
+                escape(&inline(heading.trim_start_matches('#').trim()))
+            ));
+        } else if let Some(item) = line
+            .trim_start()
+            .strip_prefix("* ")
+            .or_else(|| line.trim_start().strip_prefix("- "))
+        {
+            lines.push(format!(".IP \\(bu 2\n{}", escape_line(&inline(item))));
+        } else {
+            lines.push(escape_line(&inline(line.trim_start())));
+        }
+    }
+    while lines.last().is_some_and(|l| l == ".PP") {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// Convert inline Markdown, bold and code spans to bold and links to `text <url>`.
+fn inline(text: &str) -> String {
+    let text = text.replace("**", "`");
+    let mut result = String::new();
+    let mut bold = false;
+    let mut rest = text.as_str();
+    while let Some(c) = rest.chars().next() {
+        if c == '`' {
+            result.push_str(if bold { "\u{1}R" } else { "\u{1}B" });
+            bold = !bold;
+            rest = &rest[1..];
+        } else if c == '['
+            && let Some((label, tail)) = rest[1..].split_once("](")
+            && let Some((url, tail)) = tail.split_once(')')
+        {
+            let _ = write!(result, "{label} <{url}>");
+            rest = tail;
+        } else {
+            result.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    if bold {
+        result.push_str("\u{1}R");
+    }
+    result
+}
+
+fn escape_lines(text: &str) -> String {
+    text.lines().map(escape_line).collect::<Vec<_>>().join("\n")
+}
+
+/// Escape a line of text so that it is never interpreted as a request.
+fn escape_line(line: &str) -> String {
+    let line = escape(line);
+    if line.starts_with('.') || line.starts_with('\'') {
+        format!("\\&{line}")
+    } else {
+        line
+    }
+}
+
+/// Escape backslashes, and restore the font changes marked by [`inline`].
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\e").replace('\u{1}', "\\f")
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roff_paragraphs_and_lists() {
+        assert_eq!(
+            to_roff("First line.\n\n\n### Heading\n\n* **A**: item\n"),
+            "First line.\n.PP\n.SS Heading\n.PP\n.IP \\(bu 2\n\\fBA\\fR: item"
+        );
+    }
+
+    #[test]
+    fn roff_inline_markup() {
+        assert_eq!(
+            to_roff("Use `foo\\bar`, see [docs](https://example.com)."),
+            "Use \\fBfoo\\ebar\\fR, see docs <https://example.com>."
+        );
+    }
+
+    #[test]
+    fn roff_escapes_requests() {
+        assert_eq!(to_roff(".not a request"), "\\&.not a request");
+    }
+}
@@ -0,0 +1,151 @@
+//! Environment variables set by a plugin, and restored when it is unloaded.
+//!
+//! For each variable the `env-save` block of the plugin's `_plugin_init` function
+//! saves its current state and exports its default value, and the `env-restore`
+//! block of `_plugin_unload` returns it to that state. For plain plugins the state,
+//! unset, set, or set and exported, is saved in the plugin hash under the keys
+//! `_OLD_NAME_STATE` and `_OLD_NAME`; with zplugins the plugin manager's
+//! `@zplugins_envvar_save` and `@zplugins_envvar_restore` functions are used.
+
+use crate::{
+    blocks::{
+        B_ENV_RESTORE, B_ENV_SAVE, append_to_block, block_content, env_restore_block,
+        env_save_block,
+    },
+    error::Error,
+    manifest::update_manifest,
+    plugin::Plugin,
+    readme::update_readme,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs::read_to_string, process::ExitCode, str::FromStr};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// An environment variable, with an optional default value, managed by a plugin.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct EnvVar {
+    name: String,
+    default: Option<String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Add save and restore code for `vars` to the plugin's lifecycle functions,
+/// ignoring any variable the plugin already manages.
+pub(crate) fn add_env_vars(plugin: &Plugin, vars: &[EnvVar]) -> Result<ExitCode, Error> {
+    trace!("add_env_vars => plugin: {plugin:?}, vars: {vars:?}");
+    let plugin_file = plugin.plugin_file();
+    let source = read_to_string(&plugin_file)?;
+    let existing = block_content(&source, B_ENV_SAVE).unwrap_or_default();
+
+    let mut added: Vec<EnvVar> = Vec::default();
+    for var in vars {
+        if existing.contains(&format!("# {},", var.name()))
+            || added.iter().any(|v| v.name() == var.name())
+        {
+            eprintln!(
+                "The plugin already manages the variable '{}', ignoring.",
+                var.name()
+            );
+        } else {
+            added.push(var.clone());
+        }
+    }
+    if added.is_empty() {
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let (name, var, zplugins) = (plugin.name(), plugin.var(), plugin.uses_zplugins());
+    append_to_block(
+        &plugin_file,
+        B_ENV_SAVE,
+        &env_save_block(name, &var, zplugins, &added),
+    )?;
+    print!(".");
+    append_to_block(
+        &plugin_file,
+        B_ENV_RESTORE,
+        &env_restore_block(name, &var, zplugins, &added),
+    )?;
+    update_manifest(plugin)?;
+    update_readme(plugin)?;
+    println!(". Done");
+
+    Ok(ExitCode::SUCCESS)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl FromStr for EnvVar {
+    type Err = Error;
+
+    /// Parse a variable of the form `NAME` or `NAME=DEFAULT`, where the name is a
+    /// valid shell identifier.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, default) = match s.split_once('=') {
+            Some((name, default)) => (name, Some(default.to_string())),
+            None => (s, None),
+        };
+        let mut chars = name.chars();
+        if !chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            error!("EnvVar::from_str; value {s:?} is not a variable name");
+            return Err(Error::InvalidEnvVar {
+                value: s.to_string(),
+            });
+        }
+        Ok(Self {
+            name: name.to_string(),
+            default,
+        })
+    }
+}
+
+impl EnvVar {
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The value exported if the variable is not already set.
+    pub(crate) fn default(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_env_vars() {
+        let var: EnvVar = "EDITOR=vim -u NONE".parse().unwrap();
+        assert_eq!(var.name(), "EDITOR");
+        assert_eq!(var.default(), Some("vim -u NONE"));
+        let var: EnvVar = "_PAGER".parse().unwrap();
+        assert_eq!(var.default(), None);
+        let var: EnvVar = "EMPTY=".parse().unwrap();
+        assert_eq!(var.default(), Some(""));
+    }
+
+    #[test]
+    fn parse_invalid_env_vars() {
+        assert!("".parse::<EnvVar>().is_err());
+        assert!("1A=b".parse::<EnvVar>().is_err());
+        assert!("MY-VAR".parse::<EnvVar>().is_err());
+    }
+}
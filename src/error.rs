@@ -3,7 +3,7 @@ Provides this crate's [`Error`] and [`Result`] types as well as helper functions
 
  */
 
-use crate::name::NameErrorKind;
+use crate::{name::NameErrorKind, workspace::P_WORKSPACE};
 use flat_error::FlatError;
 use git2::Error as GitError;
 use std::{
@@ -25,15 +25,91 @@ use tracing_subscriber::filter::ParseError;
 ///
 #[derive(Clone, Debug, PartialEq)]
 pub enum Error {
-    Io { source: FlatError },
-    EnvFilter { source: FlatError },
-    SetGlobal { source: FlatError },
-    InvalidName { kind: NameErrorKind },
-    Template { source: FlatError },
-    GitInit { source: FlatError },
-    TargetExists { path: PathBuf },
-    Multiple { sources: Vec<Error> },
-    Unknown { message: String },
+    Io {
+        source: FlatError,
+    },
+    EnvFilter {
+        source: FlatError,
+    },
+    SetGlobal {
+        source: FlatError,
+    },
+    InvalidName {
+        kind: NameErrorKind,
+    },
+    Template {
+        source: FlatError,
+    },
+    GitInit {
+        source: FlatError,
+    },
+    Git {
+        source: FlatError,
+    },
+    TargetExists {
+        path: PathBuf,
+    },
+    PluginNotFound {
+        path: PathBuf,
+    },
+    MarkerNotFound {
+        path: PathBuf,
+        marker: String,
+    },
+    InvalidVersion {
+        value: String,
+    },
+    InvalidRepository {
+        value: String,
+    },
+    InvalidRequirement {
+        value: String,
+    },
+    InvalidEnvVar {
+        value: String,
+    },
+    InvalidWidgetName {
+        value: String,
+    },
+    InvalidSettingDefault {
+        name: String,
+        kind: String,
+        value: String,
+    },
+    Compile {
+        path: PathBuf,
+        message: String,
+    },
+    PackageNotFound {
+        path: PathBuf,
+    },
+    MissingMetadata {
+        tag: String,
+    },
+    TestsNotFound {
+        path: PathBuf,
+    },
+    WorkspaceNotFound {
+        path: PathBuf,
+    },
+    InvalidWorkspace {
+        path: PathBuf,
+        source: FlatError,
+    },
+    InvalidConfig {
+        path: PathBuf,
+        source: FlatError,
+    },
+    InvalidManifest {
+        path: PathBuf,
+        source: FlatError,
+    },
+    Multiple {
+        sources: Vec<Error>,
+    },
+    Unknown {
+        message: String,
+    },
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -59,9 +135,59 @@ impl Display for Error {
                 Self::GitInit { source } => format!(
                     "An error occurred initializing the new Git repository; source: {source}"
                 ),
+                Self::Git { source } =>
+                    format!("An error occurred performing a Git operation; source: {source}"),
                 Self::TargetExists { path } => format!(
                     "An error occurred generating a template: target path {path:?} already exists"
                 ),
+                Self::PluginNotFound { path } => format!(
+                    "An error occurred locating the plugin: no single '*.plugin.zsh' file in {path:?}"
+                ),
+                Self::MarkerNotFound { path, marker } => format!(
+                    "An error occurred updating a generated file: marker '{marker}' not found in {path:?}"
+                ),
+                Self::InvalidVersion { value } => format!(
+                    "An error occurred parsing a version: {value:?} is not a semantic version"
+                ),
+                Self::InvalidRepository { value } => format!(
+                    "An error occurred parsing a repository: {value:?} is not a repository URL"
+                ),
+                Self::InvalidRequirement { value } => format!(
+                    "An error occurred parsing a command requirement: {value:?} is not of the form 'NAME' or 'NAME>=VERSION'"
+                ),
+                Self::InvalidEnvVar { value } => format!(
+                    "An error occurred parsing an environment variable: {value:?} is not of the form 'NAME' or 'NAME=DEFAULT'"
+                ),
+                Self::InvalidWidgetName { value } => format!(
+                    "An error occurred parsing a widget name: {value:?} may only contain letters, digits, '-', '_' and '.'"
+                ),
+                Self::InvalidSettingDefault { name, kind, value } => format!(
+                    "An error occurred adding the setting '{name}': the default {value:?} is not a valid {kind}"
+                ),
+                Self::Compile { path, message } => format!(
+                    "An error occurred compiling {path:?} with zcompile; message: {message}"
+                ),
+                Self::PackageNotFound { path } => format!(
+                    "An error occurred reading the package checksum: {path:?} is missing or invalid"
+                ),
+                Self::MissingMetadata { tag } => format!(
+                    "An error occurred reading the plugin's metadata: the '@{tag}' tag is not set"
+                ),
+                Self::TestsNotFound { path } => format!(
+                    "An error occurred running the plugin's tests: no 'test' target in {path:?}"
+                ),
+                Self::WorkspaceNotFound { path } => format!(
+                    "An error occurred locating the workspace: no '{P_WORKSPACE}' file in {path:?} or its parents"
+                ),
+                Self::InvalidWorkspace { path, source } => format!(
+                    "An error occurred reading the workspace file {path:?}; source: {source}"
+                ),
+                Self::InvalidConfig { path, source } => format!(
+                    "An error occurred reading the configuration file {path:?}; source: {source}"
+                ),
+                Self::InvalidManifest { path, source } => format!(
+                    "An error occurred reading the plugin manifest {path:?}; source: {source}"
+                ),
                 Self::Multiple { sources } => {
                     format!(
                         "Multiple errors occurred:\n{}",
@@ -87,6 +213,10 @@ impl StdError for Error {
             Self::EnvFilter { source } => Some(source),
             Self::SetGlobal { source } => Some(source),
             Self::GitInit { source } => Some(source),
+            Self::Git { source } => Some(source),
+            Self::InvalidWorkspace { source, .. } => Some(source),
+            Self::InvalidConfig { source, .. } => Some(source),
+            Self::InvalidManifest { source, .. } => Some(source),
             _ => None,
         }
     }
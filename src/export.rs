@@ -0,0 +1,290 @@
+//! Package manager recipes for a plugin's release tarball.
+//!
+//! The `export` commands render a Homebrew formula, a Nix derivation, or a Home
+//! Manager configuration for a plugin from its manifest. The formula and derivation
+//! download the tarball written by `package` and install its contents into
+//! `share/zsh-NAME`. Unless given explicitly, the tarball's URL is that of a release
+//! asset of the plugin's repository, tagged `vVERSION`, and its checksum is read
+//! from the `.sha256` file `package` writes alongside it.
+
+use crate::{
+    error::Error,
+    manifest::Manifest,
+    package::{archive_file_name, packaged_checksum},
+    plugin::Plugin,
+    repository::RemoteRepository,
+    version::{Version, current_version},
+};
+use std::{fs::write, path::Path, process::ExitCode};
+use tera::{Context, Tera};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// The location and checksum of a plugin's release tarball, either of which is
+/// derived from the plugin if not set.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ReleaseSource {
+    pub(crate) url: Option<String>,
+    pub(crate) sha256: Option<String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Render a Homebrew formula for `plugin` to `output`, or to stdout.
+pub(crate) fn export_brew(
+    plugin: &Plugin,
+    source: &ReleaseSource,
+    output: Option<&Path>,
+) -> Result<ExitCode, Error> {
+    trace!("export_brew => plugin: {plugin:?}, source: {source:?}, output: {output:?}");
+    let (manifest, version) = (Manifest::current(plugin)?, current_version(plugin)?);
+    let mut ctx = recipe_context(plugin, &manifest, &version, ruby_escape);
+    insert_source(&mut ctx, plugin, &manifest, &version, source)?;
+    ctx.insert(V_FORMULA_CLASS, &formula_class(plugin.name()));
+    render(T_BREW_FORMULA, &ctx, output)
+}
+
+/// Render a Nix derivation for `plugin`, or if `home_manager` is set a Home Manager
+/// configuration that adds the derivation's plugin to Zsh, to `output` or stdout.
+pub(crate) fn export_nix(
+    plugin: &Plugin,
+    source: &ReleaseSource,
+    home_manager: bool,
+    output: Option<&Path>,
+) -> Result<ExitCode, Error> {
+    trace!(
+        "export_nix => plugin: {plugin:?}, source: {source:?}, home_manager: {home_manager}, output: {output:?}"
+    );
+    let (manifest, version) = (Manifest::current(plugin)?, current_version(plugin)?);
+    let mut ctx = recipe_context(plugin, &manifest, &version, nix_escape);
+    if home_manager {
+        render(T_HOME_MANAGER, &ctx, output)
+    } else {
+        insert_source(&mut ctx, plugin, &manifest, &version, source)?;
+        render(T_NIX_DERIVATION, &ctx, output)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+const T_BREW_FORMULA: &str = include_str!("templates/export/formula.rb");
+const T_NIX_DERIVATION: &str = include_str!("templates/export/package.nix");
+const T_HOME_MANAGER: &str = include_str!("templates/export/home-manager.nix");
+
+const V_DESCRIPTION: &str = "description";
+const V_FORMULA_CLASS: &str = "formula_class";
+const V_HOMEPAGE: &str = "homepage";
+const V_INSTALL_DIR: &str = "install_dir";
+const V_LICENSES: &str = "licenses";
+const V_LICENSE_OPERATOR: &str = "license_operator";
+const V_PLUGIN_DISPLAY_NAME: &str = "plugin_display_name";
+const V_PLUGIN_FILE: &str = "plugin_file";
+const V_PLUGIN_NAME: &str = "plugin_name";
+const V_SHA256: &str = "sha256";
+const V_URL: &str = "url";
+const V_VERSION: &str = "version";
+
+/// The values common to all recipes, with strings quoted in the recipe escaped by
+/// `escape`.
+fn recipe_context(
+    plugin: &Plugin,
+    manifest: &Manifest,
+    version: &Version,
+    escape: fn(&str) -> String,
+) -> Context {
+    let manifest = &manifest.plugin;
+    let mut ctx = Context::new();
+    ctx.insert(V_PLUGIN_NAME, plugin.name());
+    ctx.insert(V_PLUGIN_DISPLAY_NAME, plugin.display_name());
+    ctx.insert(V_INSTALL_DIR, &install_dir(plugin.name()));
+    ctx.insert(
+        V_PLUGIN_FILE,
+        &plugin
+            .plugin_file()
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    );
+    ctx.insert(V_VERSION, &version.to_string());
+    ctx.insert(
+        V_DESCRIPTION,
+        &manifest
+            .description
+            .as_deref()
+            .map(|d| escape(d.trim().trim_end_matches('.'))),
+    );
+    ctx.insert(
+        V_HOMEPAGE,
+        &manifest
+            .homepage
+            .as_deref()
+            .or(manifest.repository.as_deref())
+            .map(escape),
+    );
+    let (operator, licenses) = manifest
+        .license
+        .as_deref()
+        .map(spdx_licenses)
+        .unwrap_or_default();
+    ctx.insert(V_LICENSE_OPERATOR, operator);
+    ctx.insert(V_LICENSES, &licenses);
+    ctx
+}
+
+/// Add the tarball's URL and checksum to `ctx`, deriving those not set in `source`.
+fn insert_source(
+    ctx: &mut Context,
+    plugin: &Plugin,
+    manifest: &Manifest,
+    version: &Version,
+    source: &ReleaseSource,
+) -> Result<(), Error> {
+    let url = match (&source.url, &manifest.plugin.repository) {
+        (Some(url), _) => url.clone(),
+        (None, Some(repository)) => release_url(&repository.parse()?, plugin.name(), version),
+        (None, None) => {
+            error!("insert_source; no URL given and no repository to derive one from");
+            return Err(Error::MissingMetadata {
+                tag: "repository".to_string(),
+            });
+        }
+    };
+    let sha256 = match &source.sha256 {
+        Some(sha256) => sha256.clone(),
+        None => packaged_checksum(plugin, version)?,
+    };
+    ctx.insert(V_URL, &url);
+    ctx.insert(V_SHA256, &sha256);
+    Ok(())
+}
+
+fn render(template: &str, ctx: &Context, output: Option<&Path>) -> Result<ExitCode, Error> {
+    let content = Tera::default().render_str(template, ctx)?;
+    match output {
+        Some(path) => {
+            write(path, content)?;
+            println!("Wrote: {}", path.display());
+        }
+        None => print!("{content}"),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// The directory, below `share`, the plugin is installed into.
+fn install_dir(plugin_name: &str) -> String {
+    format!("zsh-{plugin_name}")
+}
+
+/// The URL of the tarball attached to the release for `version` in `repository`.
+fn release_url(repository: &RemoteRepository, plugin_name: &str, version: &Version) -> String {
+    let (tag, archive) = (version.tag_name(), archive_file_name(plugin_name, version));
+    if repository.host().contains("gitlab") {
+        format!("{}/-/releases/{tag}/downloads/{archive}", repository.url())
+    } else {
+        format!("{}/releases/download/{tag}/{archive}", repository.url())
+    }
+}
+
+/// The Homebrew formula class for the plugin, its install directory in camel case.
+fn formula_class(plugin_name: &str) -> String {
+    install_dir(plugin_name)
+        .split(['-', '_'])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// The license identifiers of an SPDX expression, and whether any one (`any_of`) or
+/// all of them (`all_of`) apply.
+fn spdx_licenses(expression: &str) -> (&'static str, Vec<String>) {
+    let licenses = expression
+        .split_whitespace()
+        .map(|word| word.trim_matches(['(', ')']))
+        .filter(|word| !word.is_empty() && !["OR", "AND"].contains(word))
+        .map(str::to_string)
+        .collect();
+    let operator = if expression.contains(" AND ") {
+        "all_of"
+    } else {
+        "any_of"
+    };
+    (operator, licenses)
+}
+
+fn ruby_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace("#{", "\\#{")
+}
+
+fn nix_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace("${", "\\${")
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formula_class_names() {
+        assert_eq!(formula_class("git-tools"), "ZshGitTools");
+        assert_eq!(formula_class("k8s_helpers"), "ZshK8sHelpers");
+    }
+
+    #[test]
+    fn license_expressions() {
+        assert_eq!(
+            spdx_licenses("MIT OR Apache-2.0"),
+            ("any_of", vec!["MIT".to_string(), "Apache-2.0".to_string()])
+        );
+        assert_eq!(
+            spdx_licenses("(MIT AND BSD-3-Clause)"),
+            (
+                "all_of",
+                vec!["MIT".to_string(), "BSD-3-Clause".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn release_urls() {
+        let version: Version = "1.2.3".parse().unwrap();
+        assert_eq!(
+            release_url(
+                &RemoteRepository::new("github.com", "me", "zsh-demo-plugin"),
+                "demo",
+                &version
+            ),
+            "https://github.com/me/zsh-demo-plugin/releases/download/v1.2.3/demo-1.2.3.tar.gz"
+        );
+        assert_eq!(
+            release_url(
+                &RemoteRepository::new("gitlab.com", "me", "zsh-demo-plugin"),
+                "demo",
+                &version
+            ),
+            "https://gitlab.com/me/zsh-demo-plugin/-/releases/v1.2.3/downloads/demo-1.2.3.tar.gz"
+        );
+    }
+}
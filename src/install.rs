@@ -0,0 +1,229 @@
+//! Installation of a plugin into the user's own shell.
+//!
+//! The `install` command links, or copies, a plugin into the plugins directory and
+//! adds a block that loads it to the user's `.zshrc`, in `$ZDOTDIR` or `$HOME`:
+//!
+//! ```zsh
+//! # zsh-plugin:begin:my-plugin
+//! source '/home/me/.local/share/zsh-plugin/plugins/my-plugin/my-plugin.plugin.zsh'
+//! # zsh-plugin:end:my-plugin
+//! ```
+//!
+//! A linked plugin reflects any later changes to its sources, which suits trying
+//! out a plugin while it is developed; a copy contains only the files `package`
+//! would include. The `uninstall` command removes both the plugin and the block.
+//! Both commands may be repeated safely, an earlier install of the same plugin is
+//! replaced, and `.zshrc` is backed up, to `.zshrc.bak-TIMESTAMP`, before each
+//! change.
+
+use crate::{
+    blocks::{add_block, block_content, install_block, remove_block, replace_block},
+    error::Error,
+    package::package_files,
+    plugin::{PLUGIN_FILE_SUFFIX, Plugin},
+};
+use chrono::Local;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::{
+    env::var_os,
+    fs::{
+        copy, create_dir_all, read_link, read_to_string, remove_dir_all, remove_file,
+        symlink_metadata, write,
+    },
+    io::ErrorKind,
+    os::unix::fs::symlink,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// How the block added to `.zshrc` loads an installed plugin.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum PluginManager {
+    /// No plugin manager, the plugin file is sourced directly.
+    #[default]
+    None,
+
+    /// Load the plugin with 'zplug', as a local plugin.
+    Zplug,
+
+    /// Load the plugin with 'antigen bundle', from its local directory.
+    Antigen,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Link, or if `copy_files` is set copy, `plugin` into `plugins_dir` and load it from
+/// the user's `.zshrc`.
+pub(crate) fn install_plugin(
+    plugin: &Plugin,
+    plugins_dir: &Path,
+    manager: PluginManager,
+    copy_files: bool,
+    force: bool,
+) -> Result<ExitCode, Error> {
+    trace!(
+        "install_plugin => plugin: {plugin:?}, plugins_dir: {plugins_dir:?}, manager: {manager:?}, copy_files: {copy_files}, force: {force}"
+    );
+    let name = plugin.name();
+    let source = plugin.root().canonicalize()?;
+    create_dir_all(plugins_dir)?;
+    let target = plugins_dir.canonicalize()?.join(name);
+
+    let plugin_file = plugin
+        .plugin_file()
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let linked = read_link(&target).is_ok_and(|link| link == source);
+    if !linked || copy_files {
+        if symlink_metadata(&target).is_ok() {
+            // Replace an earlier install of the plugin, but nothing else.
+            if !force && !target.join(&plugin_file).is_file() {
+                error!("Install target {target:?} already exists");
+                return Err(Error::TargetExists { path: target });
+            }
+            remove_installed(&target)?;
+        }
+        if copy_files {
+            for file in package_files(plugin)? {
+                let to = target.join(&file);
+                if let Some(parent) = to.parent() {
+                    create_dir_all(parent)?;
+                }
+                copy(source.join(&file), to)?;
+            }
+        } else {
+            symlink(&source, &target)?;
+        }
+    }
+    print!(".");
+
+    let block = install_block(manager, &target, &plugin_file);
+    let zshrc = zshrc_path()?;
+    let current = read_zshrc(&zshrc)?;
+    let updated = match block_content(&current, name) {
+        Some(existing) if existing == block => None,
+        Some(_) => replace_block(&current, name, &block),
+        None => Some(add_block(&current, name, &block)),
+    };
+    if let Some(updated) = updated {
+        backup_zshrc(&zshrc)?;
+        write(&zshrc, updated)?;
+    }
+    println!(". Done");
+
+    println!("Installed: {}", target.display());
+    println!("Loaded from: {}", zshrc.display());
+    println!(
+        "Start a new shell, or run 'source {}', to use it.",
+        zshrc.display()
+    );
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Remove the plugin `name` from `plugins_dir`, and its block from the user's `.zshrc`.
+pub(crate) fn uninstall_plugin(
+    name: &str,
+    plugins_dir: &Path,
+    force: bool,
+) -> Result<ExitCode, Error> {
+    trace!("uninstall_plugin => name: {name}, plugins_dir: {plugins_dir:?}, force: {force}");
+    // Install uses the plugin's file name, with '_' for any '-' in the name given to init.
+    let plugin_name = name.replace('-', "_");
+    let target = plugins_dir.join(&plugin_name);
+    let zshrc = zshrc_path()?;
+    let updated = remove_block(&read_zshrc(&zshrc)?, &plugin_name);
+    let installed = symlink_metadata(&target).is_ok();
+
+    if updated.is_none() && !installed {
+        eprintln!("The plugin '{name}' is not installed, ignoring.");
+        return Ok(ExitCode::SUCCESS);
+    }
+    // Remove what install created, a link or a copy of the plugin, but nothing else.
+    if installed
+        && !force
+        && !symlink_metadata(&target)?.is_symlink()
+        && !target
+            .join(format!("{plugin_name}{PLUGIN_FILE_SUFFIX}"))
+            .is_file()
+    {
+        error!("Uninstall target {target:?} is not an installed plugin");
+        return Err(Error::TargetExists { path: target });
+    }
+    if let Some(updated) = updated {
+        backup_zshrc(&zshrc)?;
+        write(&zshrc, updated)?;
+    }
+    print!(".");
+    if installed {
+        remove_installed(&target)?;
+    }
+    println!(". Done");
+
+    println!("Uninstalled: {name}");
+
+    Ok(ExitCode::SUCCESS)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+const P_ZSHRC: &str = ".zshrc";
+
+/// The user's `.zshrc`, in `$ZDOTDIR` if it is set, otherwise in `$HOME`.
+fn zshrc_path() -> Result<PathBuf, Error> {
+    var_os("ZDOTDIR")
+        .filter(|dir| !dir.is_empty())
+        .or_else(|| var_os("HOME"))
+        .map(|dir| PathBuf::from(dir).join(P_ZSHRC))
+        .ok_or_else(|| {
+            error!("Neither ZDOTDIR nor HOME is set");
+            Error::Unknown {
+                message: "neither ZDOTDIR nor HOME is set".to_string(),
+            }
+        })
+}
+
+/// The content of `zshrc`, which is empty if the file does not exist yet.
+fn read_zshrc(zshrc: &Path) -> Result<String, Error> {
+    match read_to_string(zshrc) {
+        Ok(content) => Ok(content),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => {
+            error!("Could not read {zshrc:?}, error: {e}");
+            Err(e.into())
+        }
+    }
+}
+
+/// Copy `zshrc`, if it exists, to `.zshrc.bak-TIMESTAMP` alongside it.
+fn backup_zshrc(zshrc: &Path) -> Result<(), Error> {
+    if zshrc.is_file() {
+        let mut backup = zshrc.as_os_str().to_owned();
+        backup.push(format!(".bak-{}", Local::now().format("%Y%m%d%H%M%S")));
+        copy(zshrc, PathBuf::from(backup))?;
+    }
+    Ok(())
+}
+
+/// Remove an installed plugin, either a link or a copied directory.
+fn remove_installed(target: &Path) -> Result<(), Error> {
+    if symlink_metadata(target)?.is_dir() {
+        remove_dir_all(target)?;
+    } else {
+        remove_file(target)?;
+    }
+    Ok(())
+}
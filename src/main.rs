@@ -6,6 +6,7 @@
 //! - Main plugin source file with function tracking and unload support
 //! - Optional autoloaded functions directory
 //! - Optional bin directory for scripts
+//! - Optional modules directory for multi-file plugins
 //! - GitHub Actions workflows for shellcheck/shellspec
 //! - Git repository initialization
 //!
@@ -13,11 +14,35 @@
 //!
 //! The crate is organized around a simple command pattern:
 //!
+//! - [`blocks`] - Maintenance of generated blocks in plugin sources
+//! - [`check`] - Syntax, lint, README and manifest checks of existing plugins
 //! - [`cli`] - Command-line argument parsing using clap
+//! - [`config`] - User configuration of default values
 //! - [`command`] - The [`OnceCommand`](command::OnceCommand) trait for executable commands
+//! - [`compile`] - Compilation of plugin sources to Zsh word code
+//! - [`dependencies`] - Plugins a plugin depends on
+//! - [`doc`] - Documentation extraction from shdoc annotations
+//! - [`environment`] - Environment variables set by a plugin
 //! - [`error`] - Error types and conversions
+//! - [`export`] - Homebrew and Nix recipes for packaged plugins
+//! - [`install`] - Installation of plugins into the user's shell
+//! - [`manifest`] - The machine-readable `plugin.toml` manifest
 //! - [`name`] - Plugin name validation
+//! - [`package`] - Release tarballs of plugins
+//! - [`plugin`] - Discovery of existing plugins
+//! - [`readme`] - Maintenance of generated README sections
+//! - [`registry`] - An index of the plugins found on the local machine
+//! - [`repository`] - Hosted repository URLs
+//! - [`requirements`] - External commands a plugin requires
+//! - [`settings`] - User settings of a plugin, read with `zstyle`
+//! - [`shell_hooks`] - Zsh hook functions registered by a plugin
+//! - [`shim`] - Fish and POSIX sh ports of a plugin's public commands
 //! - [`templates`] - Template rendering using Tera
+//! - [`test`] - Running a plugin's tests
+//! - [`upgrade`] - Upgrading plugins to the current version of this tool
+//! - [`version`] - Semantic version management
+//! - [`widgets`] - Line editor widgets added by a plugin
+//! - [`workspace`] - Workspaces of many plugins, managed together
 //!
 //! ## Example
 //!
@@ -33,11 +58,35 @@
 // Modules
 // ------------------------------------------------------------------------------------------------
 
+pub(crate) mod blocks;
+pub(crate) mod check;
 pub(crate) mod cli;
 pub(crate) mod command;
+pub(crate) mod compile;
+pub(crate) mod config;
+pub(crate) mod dependencies;
+pub(crate) mod doc;
+pub(crate) mod environment;
 pub(crate) mod error;
+pub(crate) mod export;
+pub(crate) mod install;
+pub(crate) mod manifest;
 pub(crate) mod name;
+pub(crate) mod package;
+pub(crate) mod plugin;
+pub(crate) mod readme;
+pub(crate) mod registry;
+pub(crate) mod repository;
+pub(crate) mod requirements;
+pub(crate) mod settings;
+pub(crate) mod shell_hooks;
+pub(crate) mod shim;
 pub(crate) mod templates;
+pub(crate) mod test;
+pub(crate) mod upgrade;
+pub(crate) mod version;
+pub(crate) mod widgets;
+pub(crate) mod workspace;
 
 // ------------------------------------------------------------------------------------------------
 // Imports
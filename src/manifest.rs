@@ -0,0 +1,428 @@
+//! The machine-readable plugin manifest, `plugin.toml`.
+//!
+//! The manifest records what a generated plugin is and provides, so that tooling
+//! and plugin managers can introspect a plugin without parsing shell. It is written
+//! by `init` and kept up to date by the other subcommands that change a plugin.
+//!
+//! Most of the manifest is derived from the plugin itself, the tags in the plugin
+//! file's header, the functions and aliases it defines, and the files present. Only
+//! the dependencies, settings and generator sections are retained from an existing
+//! manifest, the generator section is only changed by the `upgrade` command.
+//!
+//! # Examples
+//!
+//! ```toml
+//! [plugin]
+//! name = "containers"
+//! display-name = "containers"
+//! variable = "CONTAINERS"
+//! version = "0.1.0"
+//! license = "MIT"
+//! authors = ["Jane Doe <jane@example.com>"]
+//!
+//! [dependencies]
+//! plugins = []
+//!
+//! [provides]
+//! functions = ["containers_example"]
+//! aliases = []
+//! completions = []
+//!
+//! [components]
+//! functions-dir = true
+//! ...
+//!
+//! [generator]
+//! name = "zsh-plugin"
+//! version = "0.2.1"
+//! ```
+
+use crate::{
+    blocks::generated_helpers,
+    doc::PluginDocs,
+    error::Error,
+    plugin::Plugin,
+    requirements::CommandRequirement,
+    settings::Setting,
+    templates::{
+        P_BIN_DIR, P_DOT_GITLAB_CI, P_FORGEJO_DIR, P_GIT_HOOKS_DIR, P_GITHUB_DIR, P_MAKEFILE,
+        P_MKDOC, P_PRE_PUSH, P_README,
+    },
+};
+use flat_error::FlatError;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{read_to_string, write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// The contents of a plugin's `plugin.toml` file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub(crate) struct Manifest {
+    pub(crate) plugin: PluginSection,
+    pub(crate) dependencies: Dependencies,
+    pub(crate) provides: Provides,
+    pub(crate) components: Components,
+    pub(crate) generator: Generator,
+    /// Settings read with `zstyle`, maintained with `add setting`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) settings: Vec<Setting>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub(crate) struct PluginSection {
+    pub(crate) name: String,
+    pub(crate) display_name: String,
+    /// The name of the plugin's global associative array, and variable prefix.
+    pub(crate) variable: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) license: Option<String>,
+    pub(crate) authors: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) repository: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) homepage: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub(crate) struct Dependencies {
+    /// Other plugins that must be loaded before this one.
+    pub(crate) plugins: Vec<String>,
+    /// External commands checked for when the plugin is initialized.
+    pub(crate) commands: Vec<CommandRequirement>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub(crate) struct Provides {
+    /// Public functions, from the plugin file, modules and `functions` directory,
+    /// excluding the helpers generated for hooks, widgets and settings.
+    pub(crate) functions: Vec<String>,
+    /// Aliases defined with the plugin's `define_alias` function.
+    pub(crate) aliases: Vec<String>,
+    /// Completion functions, autoload files named `_*`.
+    pub(crate) completions: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub(crate) struct Components {
+    pub(crate) zplugins: bool,
+    pub(crate) bash_wrapper: bool,
+    pub(crate) bin_dir: bool,
+    pub(crate) functions_dir: bool,
+    pub(crate) modules: Vec<String>,
+    pub(crate) readme: bool,
+    pub(crate) shell_check: bool,
+    pub(crate) shell_doc: bool,
+    pub(crate) shell_spec: bool,
+    /// The CI service, `github`, `gitlab`, `forgejo`, or `none`.
+    pub(crate) ci: String,
+    pub(crate) git_hooks: bool,
+    pub(crate) git_pre_push_hook: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub(crate) struct Generator {
+    pub(crate) name: String,
+    pub(crate) version: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) const P_MANIFEST: &str = "plugin.toml";
+
+/// Print the plugin's manifest, derived from the plugin if no manifest exists.
+pub(crate) fn show_manifest(plugin: &Plugin) -> Result<ExitCode, Error> {
+    trace!("show_manifest => plugin: {plugin:?}");
+    let manifest = Manifest::load_or_detect(plugin)?;
+    print!("{}", manifest.to_toml()?);
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Create, or bring up to date, the plugin's manifest, or if `check` is set only
+/// report whether it is up to date.
+pub(crate) fn sync_manifest(plugin: &Plugin, check: bool) -> Result<ExitCode, Error> {
+    trace!("sync_manifest => plugin: {plugin:?}, check: {check}");
+    let current = Manifest::load(plugin)?;
+    let updated = Manifest::synced(plugin, current.as_ref())?;
+
+    if current.as_ref() == Some(&updated) {
+        println!("Manifest is up to date.");
+        Ok(ExitCode::SUCCESS)
+    } else if check {
+        eprintln!(
+            r#"Manifest is out of date.
+├─ Path: {:?}
+└─ Help: Run 'zsh-plugin manifest sync' to update the manifest."#,
+            manifest_path(plugin)
+        );
+        Ok(ExitCode::FAILURE)
+    } else {
+        updated.save(plugin)?;
+        println!("Manifest updated.");
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// Bring the plugin's manifest up to date, if it exists, without reporting
+/// progress. This is used after other commands change the plugin.
+pub(crate) fn update_manifest(plugin: &Plugin) -> Result<(), Error> {
+    if let Some(current) = Manifest::load(plugin)? {
+        let updated = Manifest::synced(plugin, Some(&current))?;
+        if updated != current {
+            updated.save(plugin)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns `true` if the plugin has no manifest, or its manifest is up to date.
+pub(crate) fn manifest_is_current(plugin: &Plugin) -> Result<bool, Error> {
+    Ok(match Manifest::load(plugin)? {
+        Some(current) => Manifest::synced(plugin, Some(&current))? == current,
+        None => true,
+    })
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self {
+            name: super::COMMAND_NAME.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+impl Manifest {
+    /// Load the plugin's manifest, if it has one.
+    pub(crate) fn load(plugin: &Plugin) -> Result<Option<Self>, Error> {
+        let path = manifest_path(plugin);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Self::read(&path).map(Some)
+    }
+
+    /// Read the manifest file at `path`, which need not belong to a plugin that
+    /// can be opened.
+    pub(crate) fn read(path: &Path) -> Result<Self, Error> {
+        toml::from_str(&read_to_string(path)?).map_err(|e| {
+            error!("Error parsing manifest {path:?}, error: {e}");
+            Error::InvalidManifest {
+                path: path.to_path_buf(),
+                source: FlatError::from_any(&e),
+            }
+        })
+    }
+
+    /// Load the plugin's manifest, or derive a new one if it has none.
+    pub(crate) fn load_or_detect(plugin: &Plugin) -> Result<Self, Error> {
+        match Self::load(plugin)? {
+            Some(manifest) => Ok(manifest),
+            None => Self::detect(plugin),
+        }
+    }
+
+    /// The manifest as `sync` would write it, updated from the plugin's content.
+    pub(crate) fn current(plugin: &Plugin) -> Result<Self, Error> {
+        Self::synced(plugin, Self::load(plugin)?.as_ref())
+    }
+
+    /// The manifest as `upgrade` would write it, as `sync` would but recording this
+    /// version of the tool as its generator.
+    pub(crate) fn upgraded(plugin: &Plugin) -> Result<Self, Error> {
+        let mut updated = Self::current(plugin)?;
+        updated.generator = Generator::default();
+        Ok(updated)
+    }
+
+    /// Derive a new manifest from the plugin's header, sources and files.
+    pub(crate) fn detect(plugin: &Plugin) -> Result<Self, Error> {
+        trace!("Manifest::detect => plugin: {plugin:?}");
+        let docs = PluginDocs::load(plugin)?;
+        let header = docs.plugin();
+        let first = |tag: &str| header.metadata(tag).next().map(str::to_string);
+
+        let root = plugin.root();
+        let makefile = read_to_string(root.join(P_MAKEFILE)).unwrap_or_default();
+        let ci = if root.join(P_GITHUB_DIR).is_dir() {
+            "github"
+        } else if root.join(P_DOT_GITLAB_CI).is_file() {
+            "gitlab"
+        } else if root.join(P_FORGEJO_DIR).is_dir() {
+            "forgejo"
+        } else {
+            "none"
+        };
+
+        let mut completions = Vec::default();
+        let mut functions = Vec::default();
+        let helpers = generated_helpers(&read_to_string(plugin.plugin_file())?);
+        for function in docs
+            .functions()
+            .filter(|f| f.is_public() && !helpers.iter().any(|h| h == f.name()))
+        {
+            if function.name().starts_with('_') {
+                completions.push(function.name().to_string());
+            } else if !functions.iter().any(|f| f == function.name()) {
+                functions.push(function.name().to_string());
+            }
+        }
+
+        Ok(Self {
+            plugin: PluginSection {
+                name: plugin.name().to_string(),
+                display_name: plugin.display_name().to_string(),
+                variable: plugin.var(),
+                version: first("version"),
+                description: header.brief().map(str::to_string),
+                license: first("license"),
+                authors: header.metadata("author").map(str::to_string).collect(),
+                repository: first("repository"),
+                homepage: first("homepage"),
+            },
+            dependencies: Dependencies::default(),
+            provides: Provides {
+                functions,
+                aliases: docs.aliases().map(|a| a.name().to_string()).collect(),
+                completions,
+            },
+            components: Components {
+                zplugins: plugin.uses_zplugins(),
+                bash_wrapper: root.join(format!("{}.bash", plugin.name())).is_file(),
+                bin_dir: root.join(P_BIN_DIR).is_dir(),
+                functions_dir: plugin.functions_dir().is_dir(),
+                modules: plugin
+                    .module_files()?
+                    .iter()
+                    .filter_map(|m| m.file_stem().map(|s| s.to_string_lossy().to_string()))
+                    .collect(),
+                readme: root.join(P_README).is_file(),
+                shell_check: makefile.contains("shellcheck"),
+                shell_doc: makefile.contains("DOC_CMD") || root.join(P_MKDOC).is_file(),
+                shell_spec: makefile.contains("shellspec"),
+                ci: ci.to_string(),
+                git_hooks: root.join(P_GIT_HOOKS_DIR).is_dir(),
+                git_pre_push_hook: root.join(P_GIT_HOOKS_DIR).join(P_PRE_PUSH).is_file(),
+            },
+            generator: Generator::default(),
+            settings: Vec::default(),
+        })
+    }
+
+    /// Write the manifest to the plugin's `plugin.toml` file.
+    pub(crate) fn save(&self, plugin: &Plugin) -> Result<(), Error> {
+        write(manifest_path(plugin), self.to_toml()?)?;
+        Ok(())
+    }
+
+    /// A freshly derived manifest, retaining the sections of `current` that
+    /// cannot be derived from the plugin.
+    fn synced(plugin: &Plugin, current: Option<&Self>) -> Result<Self, Error> {
+        let mut updated = Self::detect(plugin)?;
+        if let Some(current) = current {
+            updated.dependencies = current.dependencies.clone();
+            updated.settings = current.settings.clone();
+            updated.generator = current.generator.clone();
+        }
+        Ok(updated)
+    }
+
+    pub(crate) fn to_toml(&self) -> Result<String, Error> {
+        toml::to_string(self).map_err(|e| {
+            error!("Error serializing manifest, error: {e}");
+            Error::Unknown {
+                message: e.to_string(),
+            }
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn manifest_path(plugin: &Plugin) -> PathBuf {
+    plugin.root().join(P_MANIFEST)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::SettingType;
+
+    #[test]
+    fn round_trip() {
+        let manifest = Manifest {
+            plugin: PluginSection {
+                name: "my_plugin".into(),
+                display_name: "my-plugin".into(),
+                variable: "MY_PLUGIN".into(),
+                version: Some("0.1.0".into()),
+                authors: vec!["Jane Doe <jane@example.com>".into()],
+                ..Default::default()
+            },
+            dependencies: Dependencies {
+                plugins: vec!["shlog".into()],
+                commands: vec!["git>=2.30".parse().unwrap()],
+            },
+            provides: Provides {
+                functions: vec!["my_plugin_example".into()],
+                ..Default::default()
+            },
+            components: Components {
+                modules: vec!["core".into()],
+                ci: "github".into(),
+                ..Default::default()
+            },
+            generator: Generator::default(),
+            settings: vec![
+                Setting::new(
+                    &"color".parse().unwrap(),
+                    SettingType::String,
+                    Some("blue"),
+                    None,
+                )
+                .unwrap(),
+            ],
+        };
+        let text = manifest.to_toml().unwrap();
+        assert!(text.starts_with("[plugin]\nname = \"my_plugin\"\n"));
+        assert!(!text.contains("license"));
+        assert!(text.contains("\n[[settings]]\nname = \"color\"\ntype = \"string\"\n"));
+        assert_eq!(toml::from_str::<Manifest>(&text).unwrap(), manifest);
+    }
+
+    #[test]
+    fn missing_sections_default() {
+        let manifest: Manifest = toml::from_str("[plugin]\nname = \"x\"\n").unwrap();
+        assert_eq!(manifest.plugin.name, "x");
+        assert!(manifest.dependencies.plugins.is_empty());
+        assert_eq!(manifest.generator, Generator::default());
+    }
+}
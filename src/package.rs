@@ -0,0 +1,253 @@
+//! Packaging of a plugin as a versioned release tarball.
+//!
+//! The `package` command collects only the files a plugin needs at run time, and
+//! its documentation, into `dist/NAME-VERSION.tar.gz` with every file under the
+//! directory `NAME-VERSION`. Development files, such as tests, CI configuration,
+//! Git hooks, the `Makefile` and compiled `.zwc` files, are excluded. Included are:
+//!
+//! * the plugin file, the Bash wrapper, modules, and the `functions` directory,
+//!   which also holds any completion functions;
+//! * the `bin` directory, keeping each file's permissions;
+//! * license files, `README.md`, `CHANGELOG.md`, and the generated documentation
+//!   in the `doc`, `man` and `help` directories;
+//! * an up-to-date `plugin.toml` manifest; and
+//! * a `SHA256SUMS` file with the checksum of every other file in the archive.
+//!
+//! The SHA-256 checksum of the archive itself is written alongside it, to
+//! `NAME-VERSION.tar.gz.sha256`, in the format used by `sha256sum`, as needed by
+//! Homebrew formulas and to verify offline installs.
+
+use crate::{
+    error::Error,
+    manifest::{Manifest, P_MANIFEST},
+    plugin::Plugin,
+    templates::{P_BIN_DIR, P_DOC_DIR, P_README},
+    version::{Version, current_version},
+};
+use flate2::{Compression, write::GzEncoder};
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::Write as FmtWrite,
+    fs::{File, create_dir_all, read, read_dir, read_to_string, write},
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tar::{Builder, Header};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) const P_DIST_DIR: &str = "dist";
+
+/// Write a release tarball of `plugin`, and its checksum, into `output_dir`, which
+/// defaults to the plugin's `dist` directory.
+pub(crate) fn package_plugin(
+    plugin: &Plugin,
+    output_dir: Option<&Path>,
+) -> Result<ExitCode, Error> {
+    trace!("package_plugin => plugin: {plugin:?}, output_dir: {output_dir:?}");
+    let root = plugin.root();
+    let version = current_version(plugin)?;
+    let prefix = format!("{}-{version}", plugin.name());
+    let output_dir = output_dir
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| root.join(P_DIST_DIR));
+    create_dir_all(&output_dir)?;
+
+    let files = package_files(plugin)?;
+    print!(".");
+
+    let archive_file_name = archive_file_name(plugin.name(), &version);
+    let archive_path = output_dir.join(&archive_file_name);
+    let encoder = GzEncoder::new(File::create(&archive_path)?, Compression::default());
+    let mut archive = Builder::new(encoder);
+    let mtime = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    let mut checksums = String::new();
+    let mut add = |archive: &mut Builder<_>, name: &Path, content: &[u8], mode: u32| {
+        let _ = writeln!(checksums, "{}  {}", sha256_hex(content), name.display());
+        append(
+            archive,
+            &Path::new(&prefix).join(name),
+            content,
+            mode,
+            mtime,
+        )
+    };
+    for file in &files {
+        let path = root.join(file);
+        let mode = path.metadata()?.permissions().mode() & 0o777;
+        add(&mut archive, file, &read(&path)?, mode)?;
+    }
+    let manifest = Manifest::current(plugin)?.to_toml()?;
+    add(
+        &mut archive,
+        Path::new(P_MANIFEST),
+        manifest.as_bytes(),
+        0o644,
+    )?;
+    print!(".");
+
+    append(
+        &mut archive,
+        &Path::new(&prefix).join(P_CHECKSUMS),
+        checksums.as_bytes(),
+        0o644,
+        mtime,
+    )?;
+    archive.into_inner()?.finish()?;
+    print!(".");
+
+    let checksum = sha256_hex(&read(&archive_path)?);
+    let checksum_path = output_dir.join(format!("{archive_file_name}.{CHECKSUM_EXTENSION}"));
+    write(&checksum_path, format!("{checksum}  {archive_file_name}\n"))?;
+    println!(" Done");
+
+    println!("Package: {}", archive_path.display());
+    println!("SHA-256: {checksum}");
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// The file name of the release tarball of version `version` of the plugin `name`.
+pub(crate) fn archive_file_name(name: &str, version: &Version) -> String {
+    format!("{name}-{version}.tar.gz")
+}
+
+/// The SHA-256 checksum of the plugin's release tarball for `version`, read from
+/// the checksum file written alongside it in the plugin's `dist` directory.
+pub(crate) fn packaged_checksum(plugin: &Plugin, version: &Version) -> Result<String, Error> {
+    let path = plugin.root().join(P_DIST_DIR).join(format!(
+        "{}.{CHECKSUM_EXTENSION}",
+        archive_file_name(plugin.name(), version)
+    ));
+    let content = read_to_string(&path).map_err(|e| {
+        error!("Error reading package checksum {path:?}, error: {e}");
+        Error::PackageNotFound { path: path.clone() }
+    })?;
+    content
+        .split_whitespace()
+        .next()
+        .filter(|checksum| checksum.len() == 64)
+        .map(str::to_string)
+        .ok_or(Error::PackageNotFound { path })
+}
+
+/// The files a plugin needs at run time, and its documentation, relative to the
+/// plugin's root and in a stable order.
+pub(crate) fn package_files(plugin: &Plugin) -> Result<Vec<PathBuf>, Error> {
+    let root = plugin.root();
+    let relative = |path: &Path| path.strip_prefix(root).unwrap_or(path).to_path_buf();
+    let mut files = vec![relative(&plugin.plugin_file())];
+
+    let wrapper = PathBuf::from(format!("{}.bash", plugin.name()));
+    if root.join(&wrapper).is_file() {
+        files.push(wrapper);
+    }
+    files.extend(plugin.module_files()?.iter().map(|p| relative(p)));
+    files.extend(plugin.function_files()?.iter().map(|p| relative(p)));
+
+    let mut top_level = Vec::default();
+    for entry in read_dir(root)? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        if LICENSE_PREFIXES.iter().any(|p| name.starts_with(p))
+            || name == P_README
+            || name == P_CHANGELOG
+        {
+            top_level.push(PathBuf::from(name));
+        }
+    }
+    top_level.sort();
+    files.extend(top_level.into_iter().filter(|f| root.join(f).is_file()));
+
+    for dir in [P_BIN_DIR, P_DOC_DIR, P_MAN_DIR, P_HELP_DIR] {
+        collect_dir(root, Path::new(dir), &mut files)?;
+    }
+    Ok(files)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+const P_CHECKSUMS: &str = "SHA256SUMS";
+const CHECKSUM_EXTENSION: &str = "sha256";
+const P_CHANGELOG: &str = "CHANGELOG.md";
+const P_MAN_DIR: &str = "man";
+const P_HELP_DIR: &str = "help";
+
+const LICENSE_PREFIXES: &[&str] = &["LICENSE", "LICENCE", "COPYING"];
+
+/// Add all files below `dir`, except hidden and compiled files, to `files`.
+fn collect_dir(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+    let full = root.join(dir);
+    if !full.is_dir() {
+        return Ok(());
+    }
+    let mut entries = read_dir(&full)?
+        .map(|entry| entry.map(|e| e.file_name()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for name in entries {
+        let name_str = name.to_string_lossy();
+        if name_str.starts_with('.') || name_str.ends_with(".zwc") {
+            continue;
+        }
+        let path = dir.join(&name);
+        if root.join(&path).is_dir() {
+            collect_dir(root, &path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn append<W: std::io::Write>(
+    archive: &mut Builder<W>,
+    path: &Path,
+    content: &[u8],
+    mode: u32,
+    mtime: u64,
+) -> Result<(), Error> {
+    let mut header = Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(mode);
+    header.set_mtime(mtime);
+    header.set_cksum();
+    archive.append_data(&mut header, path, content)?;
+    Ok(())
+}
+
+fn sha256_hex(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha256_of_empty_content() {
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+}
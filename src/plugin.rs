@@ -0,0 +1,162 @@
+//! Discovery of an existing, previously generated, plugin.
+//!
+//! This module provides the [`Plugin`] type which describes a plugin directory on disk
+//! so that subcommands other than `init` can operate on it. The plugin is identified
+//! by the single file in its root directory named `NAME.plugin.zsh`.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! let plugin = Plugin::open(Path::new("zsh-containers-plugin"))?;
+//! assert_eq!(plugin.name(), "containers");
+//! assert_eq!(plugin.var(), "CONTAINERS");
+//! ```
+
+use crate::{
+    error::Error,
+    templates::{P_FUNCTIONS_DIR, P_MODULES_DIR},
+};
+use std::{
+    fs::{read_dir, read_to_string},
+    path::{Path, PathBuf},
+};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// An existing plugin, located by its main `NAME.plugin.zsh` file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Plugin {
+    root: PathBuf,
+    name: String,
+    display_name: String,
+    uses_zplugins: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Constants
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) const PLUGIN_FILE_SUFFIX: &str = ".plugin.zsh";
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Plugin {
+    /// Open the plugin whose main file is in the directory `root`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::PluginNotFound`] if there is no, or more than one, file
+    /// named `*.plugin.zsh` in `root`.
+    pub(crate) fn open(root: &Path) -> Result<Self, Error> {
+        trace!("Plugin::open => root: {root:?}");
+
+        let mut plugin_files = Vec::default();
+        if root.is_dir() {
+            for entry in read_dir(root)? {
+                let file_name = entry?.file_name().to_string_lossy().to_string();
+                if let Some(name) = file_name.strip_suffix(PLUGIN_FILE_SUFFIX)
+                    && !name.is_empty()
+                {
+                    plugin_files.push(name.to_string());
+                }
+            }
+        }
+
+        if plugin_files.len() != 1 {
+            error!("Expected one plugin file in {root:?}, found {plugin_files:?}");
+            return Err(Error::PluginNotFound {
+                path: root.to_path_buf(),
+            });
+        }
+
+        let name = plugin_files.remove(0);
+        let source = read_to_string(root.join(format!("{name}{PLUGIN_FILE_SUFFIX}")))?;
+        let display_name = source
+            .lines()
+            .find_map(|line| line.strip_prefix("# @name "))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_else(|| name.clone());
+        let uses_zplugins = source.contains("@zplugins_");
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            name,
+            display_name,
+            uses_zplugins,
+        })
+    }
+
+    /// The plugin's root directory.
+    pub(crate) fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The plugin name as used in function and file names.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The plugin name as declared by the `@name` tag in the plugin file.
+    pub(crate) fn display_name(&self) -> &str {
+        &self.display_name
+    }
+
+    /// The name of the plugin's global variable (hash).
+    pub(crate) fn var(&self) -> String {
+        self.name.to_ascii_uppercase()
+    }
+
+    /// Returns `true` if the plugin relies on the `zplugins` plugin manager.
+    pub(crate) fn uses_zplugins(&self) -> bool {
+        self.uses_zplugins
+    }
+
+    /// The path to the main plugin file.
+    pub(crate) fn plugin_file(&self) -> PathBuf {
+        self.root.join(format!("{}{PLUGIN_FILE_SUFFIX}", self.name))
+    }
+
+    /// The path to the plugin's modules directory, which may not exist.
+    pub(crate) fn modules_dir(&self) -> PathBuf {
+        self.root.join(P_MODULES_DIR)
+    }
+
+    /// The path to the plugin's autoload functions directory, which may not exist.
+    pub(crate) fn functions_dir(&self) -> PathBuf {
+        self.root.join(P_FUNCTIONS_DIR)
+    }
+
+    /// All module files, `modules/*.zsh`, in the order they are sourced.
+    pub(crate) fn module_files(&self) -> Result<Vec<PathBuf>, Error> {
+        list_files(&self.modules_dir(), |name| name.ends_with(".zsh"))
+    }
+
+    /// All autoload function files, `functions/*`, sorted by name.
+    pub(crate) fn function_files(&self) -> Result<Vec<PathBuf>, Error> {
+        list_files(&self.functions_dir(), |name| !name.starts_with('.'))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn list_files(dir: &Path, filter: impl Fn(&str) -> bool) -> Result<Vec<PathBuf>, Error> {
+    let mut files = Vec::default();
+    if dir.is_dir() {
+        for entry in read_dir(dir)? {
+            let path = entry?.path();
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if path.is_file() && filter(&name) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
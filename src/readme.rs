@@ -0,0 +1,298 @@
+//! Maintenance of the generated sections of a plugin's `README.md`.
+//!
+//! The README template includes pairs of marker comments around the content of
+//! its "Functions" and "Aliases" sections, for example:
+//!
+//! ```markdown
+//! ## Functions
+//!
+//! <!-- zsh-plugin:begin:functions -->
+//! * **my_plugin_example**: Some function that does some thing.
+//! <!-- zsh-plugin:end:functions -->
+//! ```
+//!
+//! Everything between the markers is replaced with the public functions and
+//! tracked aliases discovered in the plugin, leaving the rest of the file untouched.
+//! The helpers generated for hooks, widgets and settings, the functions defined in
+//! the plugin file's `hook-functions`, `widget-functions` and `setting-functions`
+//! blocks, are not listed, settings are documented in their own section.
+//! An optional "Requirements" section, with the markers `requirements`, lists the
+//! plugins and commands recorded in the plugin's manifest, and an optional
+//! "Settings" section, with the markers `settings`, lists its settings.
+
+use crate::{
+    blocks::generated_helpers,
+    doc::{AliasDoc, FunctionDoc, PluginDocs},
+    error::Error,
+    manifest::{Dependencies, Manifest},
+    plugin::Plugin,
+    settings::Setting,
+    templates::P_README,
+};
+use std::{
+    fmt::Write,
+    fs::{read_to_string, write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+const M_FUNCTIONS: &str = "functions";
+const M_ALIASES: &str = "aliases";
+const M_REQUIREMENTS: &str = "requirements";
+const M_SETTINGS: &str = "settings";
+
+/// Rewrite the generated sections of the plugin's README, or if `check` is set
+/// only report whether the README is up to date.
+pub(crate) fn sync_readme(plugin: &Plugin, check: bool) -> Result<ExitCode, Error> {
+    trace!("sync_readme => plugin: {plugin:?}, check: {check}");
+    let readme_path = plugin.root().join(P_README);
+    let current = read_to_string(&readme_path)?;
+    let updated = synced_readme(plugin, &readme_path, &current)?;
+
+    if updated == current {
+        println!("README is up to date.");
+        Ok(ExitCode::SUCCESS)
+    } else if check {
+        eprintln!(
+            r#"README is out of date.
+├─ Path: {readme_path:?}
+└─ Help: Run 'zsh-plugin readme sync' to update the generated sections."#
+        );
+        Ok(ExitCode::FAILURE)
+    } else {
+        write(&readme_path, updated)?;
+        println!("README updated.");
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// Rewrite the generated sections of the plugin's README, if it exists, without
+/// reporting progress. This is used after other commands change the plugin.
+pub(crate) fn update_readme(plugin: &Plugin) -> Result<(), Error> {
+    let readme_path = plugin.root().join(P_README);
+    if readme_path.is_file() {
+        let current = read_to_string(&readme_path)?;
+        let updated = synced_readme(plugin, &readme_path, &current)?;
+        if updated != current {
+            write(&readme_path, updated)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns `true` if the generated sections of the plugin's README are up to date.
+pub(crate) fn readme_is_current(plugin: &Plugin) -> Result<bool, Error> {
+    let readme_path = plugin.root().join(P_README);
+    let current = read_to_string(&readme_path)?;
+    Ok(synced_readme(plugin, &readme_path, &current)? == current)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn synced_readme(plugin: &Plugin, readme_path: &Path, current: &str) -> Result<String, Error> {
+    let docs = PluginDocs::load(plugin)?;
+
+    let helpers = generated_helpers(&read_to_string(plugin.plugin_file())?);
+    let functions = docs
+        .functions()
+        .filter(|f| f.is_public() && !helpers.iter().any(|h| h == f.name()))
+        .collect::<Vec<_>>();
+    let content = replace_between_markers(current, M_FUNCTIONS, &functions_list(&functions))
+        .ok_or_else(|| marker_not_found(readme_path, M_FUNCTIONS))?;
+
+    let aliases = docs.aliases().collect::<Vec<_>>();
+    let content = replace_between_markers(&content, M_ALIASES, &aliases_list(&aliases))
+        .ok_or_else(|| marker_not_found(readme_path, M_ALIASES))?;
+
+    let manifest = Manifest::load(plugin)?.unwrap_or_default();
+    let content = replace_between_markers(
+        &content,
+        M_REQUIREMENTS,
+        &requirements_list(&manifest.dependencies),
+    )
+    .unwrap_or(content);
+    Ok(replace_between_markers(
+        &content,
+        M_SETTINGS,
+        &settings_list(plugin, &manifest.settings),
+    )
+    .unwrap_or(content))
+}
+
+fn functions_list(functions: &[&FunctionDoc]) -> String {
+    let mut list = String::new();
+    for function in functions {
+        match function.description().map(first_paragraph) {
+            Some(description) => {
+                let _ = writeln!(list, "* **{}**: {description}", function.name());
+            }
+            None => {
+                let _ = writeln!(list, "* **{}**", function.name());
+            }
+        }
+    }
+    if list.is_empty() {
+        list.push_str("None.\n");
+    }
+    list
+}
+
+fn aliases_list(aliases: &[&AliasDoc]) -> String {
+    let mut list = String::new();
+    for alias in aliases {
+        match alias.description().map(first_paragraph) {
+            Some(description) => {
+                let _ = writeln!(
+                    list,
+                    "* **{}**: {description} (`{}`)",
+                    alias.name(),
+                    alias.value()
+                );
+            }
+            None => {
+                let _ = writeln!(list, "* **{}**: `{}`", alias.name(), alias.value());
+            }
+        }
+    }
+    if list.is_empty() {
+        list.push_str("None.\n");
+    }
+    list
+}
+
+fn requirements_list(dependencies: &Dependencies) -> String {
+    let mut list = String::new();
+    for plugin in &dependencies.plugins {
+        let _ = writeln!(list, "* The plugin **{plugin}**, loaded first.");
+    }
+    for command in &dependencies.commands {
+        match command.min_version() {
+            Some(version) => {
+                let _ = writeln!(
+                    list,
+                    "* The command **{}**, version {version} or later.",
+                    command.name()
+                );
+            }
+            None => {
+                let _ = writeln!(list, "* The command **{}**.", command.name());
+            }
+        }
+    }
+    if list.is_empty() {
+        list.push_str("None.\n");
+    }
+    list
+}
+
+fn settings_list(plugin: &Plugin, settings: &[Setting]) -> String {
+    let mut list = String::new();
+    for setting in settings {
+        let _ = write!(
+            list,
+            "* **{}**: `zstyle ':plugin:{}' {} VALUE`, {}.",
+            setting.name(),
+            plugin.name(),
+            setting.name(),
+            setting.summary()
+        );
+        match setting.description() {
+            Some(description) => {
+                let _ = writeln!(list, " {}.", description.trim_end_matches('.'));
+            }
+            None => list.push('\n'),
+        }
+    }
+    if list.is_empty() {
+        list.push_str("None.\n");
+    }
+    list
+}
+
+fn first_paragraph(text: &str) -> String {
+    text.split("\n\n")
+        .next()
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn begin_marker(name: &str) -> String {
+    format!("<!-- zsh-plugin:begin:{name} -->")
+}
+
+fn end_marker(name: &str) -> String {
+    format!("<!-- zsh-plugin:end:{name} -->")
+}
+
+/// Replace the text between the begin and end markers `name`, returning `None`
+/// if either marker is missing.
+fn replace_between_markers(content: &str, name: &str, replacement: &str) -> Option<String> {
+    let begin = begin_marker(name);
+    let end = end_marker(name);
+    let start = content.find(&begin)? + begin.len();
+    let finish = start + content[start..].find(&end)?;
+    Some(format!(
+        "{}\n{replacement}{}",
+        &content[..start],
+        &content[finish..]
+    ))
+}
+
+fn marker_not_found(path: &Path, name: &str) -> Error {
+    error!("README {path:?} has no '{name}' markers");
+    Error::MarkerNotFound {
+        path: PathBuf::from(path),
+        marker: begin_marker(name),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replace_markers() {
+        let content = "# Title\n\n<!-- zsh-plugin:begin:functions -->\nTBD\n<!-- zsh-plugin:end:functions -->\n\nMore.\n";
+        assert_eq!(
+            replace_between_markers(content, "functions", "* **f**\n").unwrap(),
+            "# Title\n\n<!-- zsh-plugin:begin:functions -->\n* **f**\n<!-- zsh-plugin:end:functions -->\n\nMore.\n"
+        );
+    }
+
+    #[test]
+    fn replace_markers_is_idempotent() {
+        let content = "<!-- zsh-plugin:begin:aliases -->\nNone.\n<!-- zsh-plugin:end:aliases -->\n";
+        assert_eq!(
+            replace_between_markers(content, "aliases", "None.\n").unwrap(),
+            content
+        );
+    }
+
+    #[test]
+    fn replace_missing_markers() {
+        assert_eq!(replace_between_markers("# Title\n", "functions", ""), None);
+        assert_eq!(
+            replace_between_markers("<!-- zsh-plugin:begin:functions -->", "functions", ""),
+            None
+        );
+    }
+
+    #[test]
+    fn first_paragraph_only() {
+        assert_eq!(first_paragraph("One\ntwo.\n\nThree."), "One two.");
+    }
+}
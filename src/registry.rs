@@ -0,0 +1,269 @@
+//! An index of the plugins found on the local machine.
+//!
+//! The `list` and `search` commands discover plugins below a set of root
+//! directories, by default those of the `plugin-roots` configuration key, or the
+//! current directory. A directory is a plugin if it contains a `*.plugin.zsh`
+//! file, or a `plugin.toml` manifest; directories below a plugin,
+//! and hidden directories, are not searched.
+//!
+//! Each plugin is described by its name, version, description (its `@brief` tag)
+//! and public functions, derived from its sources or, failing that, read from its
+//! manifest. The `list` command also runs the `check` command's checks against
+//! each plugin, in parallel, and shows their combined outcome. Both commands
+//! write either a table or, with `--json`, a JSON array.
+
+use crate::{
+    check::{CheckStatus, check_status},
+    error::Error,
+    manifest::{Manifest, P_MANIFEST},
+    plugin::{PLUGIN_FILE_SUFFIX, Plugin},
+    workspace::run_all,
+};
+use serde::Serialize;
+use std::{
+    fs::read_dir,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+use tracing::{error, trace, warn};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// A plugin found below one of the roots.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct PluginEntry {
+    name: String,
+    path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    functions: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checks: Option<CheckStatus>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// List every plugin below `roots`, with the combined outcome of its checks.
+pub(crate) fn list_plugins(roots: &[PathBuf], json: bool) -> Result<ExitCode, Error> {
+    trace!("list_plugins => roots: {roots:?}, json: {json}");
+    let dirs = discover(roots)?;
+    let entries = run_all(&dirs, |dir| {
+        let mut entry = PluginEntry::load(dir)?;
+        if has_plugin_file(dir) {
+            entry.checks = Some(check_status(&Plugin::open(dir)?)?);
+        }
+        Ok(entry)
+    });
+    report(&readable(&dirs, entries), json)
+}
+
+/// List the plugins below `roots` whose name, description, or any function name,
+/// contains `term`, ignoring case.
+pub(crate) fn search_plugins(roots: &[PathBuf], term: &str, json: bool) -> Result<ExitCode, Error> {
+    trace!("search_plugins => roots: {roots:?}, term: {term:?}, json: {json}");
+    let dirs = discover(roots)?;
+    let entries = dirs.iter().map(|dir| PluginEntry::load(dir)).collect();
+    let entries = readable(&dirs, entries)
+        .into_iter()
+        .filter(|entry| entry.matches(term))
+        .collect::<Vec<_>>();
+    if entries.is_empty() && !json {
+        eprintln!("No plugins match '{term}'.");
+        return Ok(ExitCode::SUCCESS);
+    }
+    report(&entries, json)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl PluginEntry {
+    /// Describe the plugin in `dir`, from its sources if it can be opened, otherwise
+    /// from its manifest.
+    fn load(dir: &Path) -> Result<Self, Error> {
+        let manifest = if has_plugin_file(dir) {
+            Manifest::current(&Plugin::open(dir)?)?
+        } else {
+            Manifest::read(&dir.join(P_MANIFEST))?
+        };
+        Ok(Self {
+            name: manifest.plugin.name,
+            path: dir.to_path_buf(),
+            version: manifest.plugin.version,
+            description: manifest.plugin.description,
+            functions: manifest.provides.functions,
+            checks: None,
+        })
+    }
+
+    /// Returns `true` if the name, description or a function name contains `term`,
+    /// ignoring case.
+    fn matches(&self, term: &str) -> bool {
+        let term = term.to_lowercase();
+        let contains = |value: &str| value.to_lowercase().contains(&term);
+        contains(&self.name)
+            || self.description.as_deref().is_some_and(contains)
+            || self.functions.iter().any(|f| contains(f))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+/// The deepest directory below a root that is searched for plugins.
+const MAX_DEPTH: usize = 4;
+
+/// The plugin directories below `roots`, in a stable order.
+fn discover(roots: &[PathBuf]) -> Result<Vec<PathBuf>, Error> {
+    let mut found = Vec::default();
+    for root in roots {
+        if root.is_dir() {
+            discover_in(root, 0, &mut found)?;
+        } else {
+            warn!("Plugin root {root:?} is not a directory, ignoring");
+        }
+    }
+    Ok(found)
+}
+
+/// Search `dir` for plugins, skipping any directory below a root that cannot be
+/// read, so only an unreadable root is an error.
+fn discover_in(dir: &Path, depth: usize, found: &mut Vec<PathBuf>) -> Result<(), Error> {
+    if has_plugin_file(dir) || dir.join(P_MANIFEST).is_file() {
+        found.push(dir.to_path_buf());
+        return Ok(());
+    }
+    if depth == MAX_DEPTH {
+        return Ok(());
+    }
+    let entries = match read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if depth == 0 => {
+            error!("Error reading plugin root {dir:?}, error: {e}");
+            return Err(e.into());
+        }
+        Err(e) => {
+            warn!("Error reading directory {dir:?}, ignoring; error: {e}");
+            return Ok(());
+        }
+    };
+    let mut children = entries
+        .filter_map(Result::ok)
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect::<Vec<_>>();
+    children.sort();
+    for child in children {
+        discover_in(&child, depth + 1, found)?;
+    }
+    Ok(())
+}
+
+/// Returns `true` if `dir` contains a `*.plugin.zsh` file.
+fn has_plugin_file(dir: &Path) -> bool {
+    read_dir(dir).is_ok_and(|mut entries| {
+        entries.any(|entry| {
+            entry.is_ok_and(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .strip_suffix(PLUGIN_FILE_SUFFIX)
+                    .is_some_and(|name| !name.is_empty())
+            })
+        })
+    })
+}
+
+/// The entries that could be read, reporting the plugins that could not.
+fn readable(dirs: &[PathBuf], entries: Vec<Result<PluginEntry, Error>>) -> Vec<PluginEntry> {
+    dirs.iter()
+        .zip(entries)
+        .filter_map(|(dir, entry)| match entry {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                eprintln!("Could not read the plugin in {dir:?}, ignoring; error: {e}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Write `entries` as a table, or as JSON.
+fn report(entries: &[PluginEntry], json: bool) -> Result<ExitCode, Error> {
+    if json {
+        let content = serde_json::to_string_pretty(entries).map_err(|e| {
+            error!("Error serializing plugin list, error: {e}");
+            Error::Unknown {
+                message: e.to_string(),
+            }
+        })?;
+        println!("{content}");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let with_checks = entries.iter().any(|e| e.checks.is_some());
+    let mut rows =
+        vec![["NAME", "VERSION", "FUNCTIONS", "CHECKS", "DESCRIPTION"].map(str::to_string)];
+    rows.extend(entries.iter().map(|entry| {
+        [
+            entry.name.clone(),
+            entry.version.clone().unwrap_or_default(),
+            entry.functions.len().to_string(),
+            entry.checks.map(|c| c.to_string()).unwrap_or_default(),
+            entry.description.clone().unwrap_or_default(),
+        ]
+    }));
+    let widths: Vec<usize> = (0..4)
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].len())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    for row in rows {
+        let mut line = String::new();
+        for (i, width) in widths.iter().enumerate() {
+            if i != 3 || with_checks {
+                line.push_str(&format!("{:<width$}  ", row[i]));
+            }
+        }
+        line.push_str(&row[4]);
+        println!("{}", line.trim_end());
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_search_terms() {
+        let entry = PluginEntry {
+            name: "git-tools".to_string(),
+            path: PathBuf::from("zsh-git-tools-plugin"),
+            version: Some("0.1.0".to_string()),
+            description: Some("Helpers for Git worktrees".to_string()),
+            functions: vec!["git_tools_prune".to_string()],
+            checks: None,
+        };
+        assert!(entry.matches("GIT"));
+        assert!(entry.matches("worktree"));
+        assert!(entry.matches("prune"));
+        assert!(!entry.matches("docker"));
+    }
+}
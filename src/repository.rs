@@ -0,0 +1,221 @@
+//! Forge-agnostic description of a plugin's hosted repository.
+//!
+//! This module provides the [`RemoteRepository`] type which records the forge host,
+//! owner and repository name from which all repository URLs in generated files,
+//! the plugin header, README badges and the Git remote, are rendered.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! let repository: RemoteRepository = "git@gitlab.com:me/zsh-foo-plugin.git".parse()?;
+//! assert_eq!(repository.host(), "gitlab.com");
+//! assert_eq!(repository.owner(), "me");
+//! assert_eq!(repository.url(), "https://gitlab.com/me/zsh-foo-plugin");
+//! ```
+
+use crate::error::Error;
+use git2::Repository;
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    path::Path,
+    str::FromStr,
+};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// A repository hosted on a forge such as Github, GitLab or Forgejo.
+///
+/// The owner may contain `/` separated groups, as used by GitLab.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct RemoteRepository {
+    host: String,
+    owner: String,
+    name: String,
+    homepage: Option<String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for RemoteRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.url())
+    }
+}
+
+impl FromStr for RemoteRepository {
+    type Err = Error;
+
+    /// Parse a repository from any of the following URL forms, with an optional
+    /// `.git` suffix:
+    ///
+    /// * `https://HOST/OWNER/NAME`
+    /// * `ssh://git@HOST/OWNER/NAME`
+    /// * `git@HOST:OWNER/NAME`
+    /// * `HOST/OWNER/NAME`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            error!("RemoteRepository::from_str; value {s:?} is not a repository URL");
+            Error::InvalidRepository {
+                value: s.to_string(),
+            }
+        };
+
+        let value = s.trim().trim_end_matches('/');
+        let value = value.strip_suffix(".git").unwrap_or(value);
+        let path = match value.split_once("://") {
+            Some((_, rest)) => rest.to_string(),
+            None => match value.split_once(':') {
+                Some((host, rest)) => format!("{host}/{rest}"),
+                None => value.to_string(),
+            },
+        };
+        let path = path.rsplit_once('@').map(|(_, p)| p).unwrap_or(&path);
+
+        let (host, rest) = path.split_once('/').ok_or_else(invalid)?;
+        let (owner, name) = rest.rsplit_once('/').ok_or_else(invalid)?;
+        let host = host.split_once(':').map(|(h, _)| h).unwrap_or(host);
+        if host.is_empty()
+            || owner.is_empty()
+            || name.is_empty()
+            || owner.split('/').any(str::is_empty)
+        {
+            return Err(invalid());
+        }
+        Ok(Self::new(host, owner, name))
+    }
+}
+
+impl RemoteRepository {
+    /// Construct a repository from its parts.
+    pub(crate) fn new(host: &str, owner: &str, name: &str) -> Self {
+        Self {
+            host: host.to_string(),
+            owner: owner.to_string(),
+            name: name.to_string(),
+            homepage: None,
+        }
+    }
+
+    /// Derive the repository from the `origin` remote of the Git repository
+    /// at `path`, if there is one.
+    pub(crate) fn from_git_remote(path: &Path) -> Option<Self> {
+        trace!("RemoteRepository::from_git_remote => path: {path:?}");
+        let repository = Repository::open(path).ok()?;
+        let remote = repository.find_remote("origin").ok()?;
+        remote.url()?.parse().ok()
+    }
+
+    /// Set a homepage for the plugin that differs from the repository URL.
+    pub(crate) fn with_homepage(mut self, homepage: Option<&str>) -> Self {
+        self.homepage = homepage.map(str::to_string);
+        self
+    }
+
+    /// The forge host name, e.g. `github.com`.
+    pub(crate) fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// The owning user, organization or group.
+    pub(crate) fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// The repository name.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The homepage, if different from the repository URL.
+    pub(crate) fn homepage(&self) -> Option<&str> {
+        self.homepage.as_deref()
+    }
+
+    /// The web URL of the repository.
+    pub(crate) fn url(&self) -> String {
+        format!("https://{}/{}/{}", self.host, self.owner, self.name)
+    }
+
+    /// The URL used to clone, and push to, the repository.
+    pub(crate) fn clone_url(&self) -> String {
+        format!("{}.git", self.url())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(value: &str) -> (String, String, String) {
+        let repository: RemoteRepository = value.parse().unwrap();
+        (
+            repository.host().to_string(),
+            repository.owner().to_string(),
+            repository.name().to_string(),
+        )
+    }
+
+    #[test]
+    fn parse_https_url() {
+        assert_eq!(
+            parts("https://github.com/me/zsh-foo-plugin"),
+            ("github.com".into(), "me".into(), "zsh-foo-plugin".into())
+        );
+        assert_eq!(
+            parts("https://codeberg.org/me/zsh-foo-plugin.git/"),
+            ("codeberg.org".into(), "me".into(), "zsh-foo-plugin".into())
+        );
+    }
+
+    #[test]
+    fn parse_ssh_urls() {
+        assert_eq!(
+            parts("git@gitlab.com:group/sub/zsh-foo-plugin.git"),
+            (
+                "gitlab.com".into(),
+                "group/sub".into(),
+                "zsh-foo-plugin".into()
+            )
+        );
+        assert_eq!(
+            parts("ssh://git@git.example.org:2222/me/foo.git"),
+            ("git.example.org".into(), "me".into(), "foo".into())
+        );
+    }
+
+    #[test]
+    fn parse_bare_path() {
+        assert_eq!(
+            parts("github.com/me/foo"),
+            ("github.com".into(), "me".into(), "foo".into())
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!("foo".parse::<RemoteRepository>().is_err());
+        assert!("github.com/foo".parse::<RemoteRepository>().is_err());
+        assert!(
+            "https://github.com//foo"
+                .parse::<RemoteRepository>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn render_urls() {
+        let repository = RemoteRepository::new("gitlab.com", "me", "foo");
+        assert_eq!(repository.url(), "https://gitlab.com/me/foo");
+        assert_eq!(repository.clone_url(), "https://gitlab.com/me/foo.git");
+        assert_eq!(repository.to_string(), "https://gitlab.com/me/foo");
+    }
+}
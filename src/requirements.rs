@@ -0,0 +1,240 @@
+//! The external commands a plugin requires.
+//!
+//! Plugins often wrap command-line tools such as `git`, `fzf` or `jq`. Required
+//! commands are recorded in the `[[dependencies.commands]]` tables of the plugin's
+//! `plugin.toml` manifest, and from these the `requirements` block of the plugin's
+//! `_plugin_init` function is rendered. For each command the block checks that it
+//! is on the `PATH`, with `$+commands[NAME]`, and if a minimum version is set that
+//! the first version number in the output of `NAME --version` is at least that
+//! version, using Zsh's `is-at-least` function.
+//!
+//! When a check fails the plugin either warns and continues, skips loading
+//! silently, or reports an error and does not load, see [`OnMissing`].
+//!
+//! # Examples
+//!
+//! ```toml
+//! [[dependencies.commands]]
+//! name = "git"
+//! min-version = "2.30"
+//! on-missing = "error"
+//! ```
+
+use crate::{
+    blocks::{B_REQUIREMENTS, requirements_block, update_block},
+    error::Error,
+    manifest::Manifest,
+    plugin::Plugin,
+    readme::update_readme,
+};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    process::ExitCode,
+    str::FromStr,
+};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// An external command required by a plugin.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct CommandRequirement {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_version: Option<String>,
+    #[serde(default)]
+    on_missing: OnMissing,
+}
+
+/// What a plugin does when a required command is missing or too old.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum OnMissing {
+    /// Print a warning and continue loading the plugin.
+    #[default]
+    Warn,
+
+    /// Silently skip loading the plugin.
+    Skip,
+
+    /// Print an error and do not load the plugin.
+    Error,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Add, or replace, the commands that `plugin` requires.
+pub(crate) fn add_requirements(
+    plugin: &Plugin,
+    requirements: &[CommandRequirement],
+) -> Result<ExitCode, Error> {
+    trace!("add_requirements => plugin: {plugin:?}, requirements: {requirements:?}");
+    let mut manifest = Manifest::load_or_detect(plugin)?;
+    let commands = &mut manifest.dependencies.commands;
+    for requirement in requirements {
+        match commands.iter_mut().find(|c| c.name == requirement.name) {
+            Some(existing) => *existing = requirement.clone(),
+            None => commands.push(requirement.clone()),
+        }
+    }
+    write_requirements(plugin, &manifest)
+}
+
+/// Remove the commands `names` from those that `plugin` requires.
+pub(crate) fn remove_requirements(plugin: &Plugin, names: &[String]) -> Result<ExitCode, Error> {
+    trace!("remove_requirements => plugin: {plugin:?}, names: {names:?}");
+    let mut manifest = Manifest::load_or_detect(plugin)?;
+    let commands = &mut manifest.dependencies.commands;
+    for name in names {
+        if !commands.iter().any(|c| &c.name == name) {
+            eprintln!("The plugin does not require the command '{name}', ignoring.");
+        }
+    }
+    commands.retain(|c| !names.contains(&c.name));
+    write_requirements(plugin, &manifest)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for CommandRequirement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.min_version {
+            Some(version) => write!(f, "{}>={version}", self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl FromStr for CommandRequirement {
+    type Err = Error;
+
+    /// Parse a requirement of the form `NAME` or `NAME>=VERSION`, where the version
+    /// is one or more dot-separated numbers.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            error!("CommandRequirement::from_str; value {s:?} is not a command requirement");
+            Error::InvalidRequirement {
+                value: s.to_string(),
+            }
+        };
+
+        let (name, min_version) = match s.split_once(">=") {
+            Some((name, version)) => (name.trim(), Some(version.trim())),
+            None => (s.trim(), None),
+        };
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "._+-".contains(c))
+        {
+            return Err(invalid());
+        }
+        if let Some(version) = min_version
+            && version
+                .split('.')
+                .any(|part| part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()))
+        {
+            return Err(invalid());
+        }
+        Ok(Self::new(name, min_version))
+    }
+}
+
+impl CommandRequirement {
+    /// Construct a requirement, which warns when the command is missing.
+    pub(crate) fn new(name: &str, min_version: Option<&str>) -> Self {
+        Self {
+            name: name.to_string(),
+            min_version: min_version.map(str::to_string),
+            on_missing: OnMissing::default(),
+        }
+    }
+
+    /// Set the behavior when the command is missing or too old.
+    pub(crate) fn with_on_missing(mut self, on_missing: OnMissing) -> Self {
+        self.on_missing = on_missing;
+        self
+    }
+
+    /// The command name, as found on the `PATH`.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The minimum version required, if any.
+    pub(crate) fn min_version(&self) -> Option<&str> {
+        self.min_version.as_deref()
+    }
+
+    pub(crate) fn on_missing(&self) -> OnMissing {
+        self.on_missing
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn write_requirements(plugin: &Plugin, manifest: &Manifest) -> Result<ExitCode, Error> {
+    let commands = &manifest.dependencies.commands;
+    update_block(
+        &plugin.plugin_file(),
+        B_REQUIREMENTS,
+        &requirements_block(plugin.display_name(), commands),
+    )?;
+    print!(".");
+    manifest.save(plugin)?;
+    update_readme(plugin)?;
+    println!(" Done");
+
+    if commands.is_empty() {
+        println!("No required commands.");
+    } else {
+        println!(
+            "Requires: {}",
+            commands
+                .iter()
+                .map(CommandRequirement::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_requirements() {
+        let requirement: CommandRequirement = "git".parse().unwrap();
+        assert_eq!(requirement, CommandRequirement::new("git", None));
+        let requirement: CommandRequirement = "git>=2.30".parse().unwrap();
+        assert_eq!(requirement.name(), "git");
+        assert_eq!(requirement.min_version(), Some("2.30"));
+        assert_eq!(requirement.to_string(), "git>=2.30");
+    }
+
+    #[test]
+    fn parse_invalid_requirements() {
+        assert!("".parse::<CommandRequirement>().is_err());
+        assert!("rm -rf".parse::<CommandRequirement>().is_err());
+        assert!("git>=".parse::<CommandRequirement>().is_err());
+        assert!("git>=2.x".parse::<CommandRequirement>().is_err());
+    }
+}
@@ -0,0 +1,405 @@
+//! User settings of a plugin, read with `zstyle`.
+//!
+//! Rather than global variables, a plugin's users configure it with styles in the
+//! context `:plugin:NAME`, for example:
+//!
+//! ```zsh
+//! zstyle ':plugin:my_plugin' color 'blue'
+//! ```
+//!
+//! For each setting a lookup helper, `NAME_setting_SETTING`, is added to the plugin
+//! file between the `setting-functions` markers; it prints the setting's value, or
+//! for a boolean setting returns it as its exit status, falling back to the default
+//! when the style is not set. Settings are recorded in the `[[settings]]` tables of
+//! the plugin's `plugin.toml` manifest, from which the README's "Settings" section
+//! is generated, and each is documented in the "Public Variables" section of the
+//! plugin file's header. If the plugin uses shellspec, test cases for the default
+//! and an overridden value are added to `spec/NAME_spec.sh`.
+
+use crate::{
+    blocks::{B_SETTING_FUNCTIONS, append_to_block, setting_function_block},
+    error::Error,
+    manifest::{Manifest, update_manifest},
+    name::Name,
+    plugin::Plugin,
+    readme::update_readme,
+};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult, Write},
+    fs::{create_dir_all, read_to_string, write},
+    process::ExitCode,
+};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// A setting of a plugin, read from the style of the same name.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct Setting {
+    name: String,
+    #[serde(rename = "type")]
+    kind: SettingType,
+    #[serde(default)]
+    default: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+}
+
+/// The type of a setting's value, which determines how it is read with `zstyle`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SettingType {
+    /// A boolean, `true`, `yes`, `on` or `1` when set, read with `zstyle -t`.
+    Bool,
+
+    /// A single string, read with `zstyle -s`.
+    #[default]
+    String,
+
+    /// An integer, read with `zstyle -s` and ignored unless it is a number.
+    Int,
+
+    /// A list of strings, read with `zstyle -a`; a default is comma-separated.
+    Array,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+const P_SPEC_DIR: &str = "spec";
+
+/// Add `setting` to the plugin, with a lookup helper, documentation, and, if
+/// the plugin uses shellspec, test cases.
+pub(crate) fn add_setting(plugin: &Plugin, setting: &Setting) -> Result<ExitCode, Error> {
+    trace!("add_setting => plugin: {plugin:?}, setting: {setting:?}");
+    let mut manifest = Manifest::load_or_detect(plugin)?;
+    if manifest.settings.iter().any(|s| s.name == setting.name) {
+        eprintln!(
+            "The plugin already has the setting '{}', ignoring.",
+            setting.name
+        );
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let plugin_file = plugin.plugin_file();
+    let name = plugin.name();
+    append_to_block(
+        &plugin_file,
+        B_SETTING_FUNCTIONS,
+        &setting_function_block(name, plugin.uses_zplugins(), setting),
+    )?;
+    print!(".");
+
+    let source = read_to_string(&plugin_file)?;
+    match document_setting(&source, &setting.header_doc(name)) {
+        Some(updated) => write(&plugin_file, updated)?,
+        None => {
+            eprintln!("The plugin file has no 'Public Variables' section, not documented.");
+        }
+    }
+    print!(".");
+
+    if manifest.components.shell_spec {
+        let spec_dir = plugin.root().join(P_SPEC_DIR);
+        create_dir_all(&spec_dir)?;
+        let spec_file = spec_dir.join(format!("{name}_spec.sh"));
+        let mut spec =
+            read_to_string(&spec_file).unwrap_or_else(|_| "# shellcheck shell=sh\n".to_string());
+        spec.push_str(&setting.spec_cases(name));
+        write(&spec_file, spec)?;
+    }
+    print!(".");
+
+    manifest.settings.push(setting.clone());
+    manifest.save(plugin)?;
+    update_manifest(plugin)?;
+    update_readme(plugin)?;
+    println!(" Done");
+
+    println!("Added setting helper: {}", setting.function_name(name));
+
+    Ok(ExitCode::SUCCESS)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for SettingType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Bool => "bool",
+                Self::String => "string",
+                Self::Int => "int",
+                Self::Array => "array",
+            }
+        )
+    }
+}
+
+impl Setting {
+    /// Construct a setting, checking that `default`, if given, is a value of `kind`.
+    pub(crate) fn new(
+        name: &Name,
+        kind: SettingType,
+        default: Option<&str>,
+        description: Option<&str>,
+    ) -> Result<Self, Error> {
+        let default = match (kind, default) {
+            (SettingType::Bool, None) => "false",
+            (SettingType::Int, None) => "0",
+            (_, None) => "",
+            (SettingType::Bool, Some(value)) if bool_value(value).is_none() => {
+                return Err(invalid_default(name, kind, value));
+            }
+            (SettingType::Int, Some(value)) if value.parse::<i64>().is_err() => {
+                return Err(invalid_default(name, kind, value));
+            }
+            (_, Some(value)) => value,
+        };
+        Ok(Self {
+            name: name.to_string(),
+            kind,
+            default: default.to_string(),
+            description: description.map(str::to_string),
+        })
+    }
+
+    /// The style name, as used with `zstyle`.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn kind(&self) -> SettingType {
+        self.kind
+    }
+
+    /// The default value, the elements of an array default are comma-separated.
+    pub(crate) fn default(&self) -> &str {
+        &self.default
+    }
+
+    /// The default value of a boolean setting.
+    pub(crate) fn default_bool(&self) -> bool {
+        bool_value(&self.default).unwrap_or_default()
+    }
+
+    /// The elements of the default value of an array setting.
+    pub(crate) fn default_array(&self) -> Vec<&str> {
+        if self.default.is_empty() {
+            Vec::default()
+        } else {
+            self.default.split(',').map(str::trim).collect()
+        }
+    }
+
+    pub(crate) fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// The name of the plugin's function that looks up this setting.
+    pub(crate) fn function_name(&self, plugin_name: &str) -> String {
+        format!("{plugin_name}_setting_{}", self.name.replace('-', "_"))
+    }
+
+    /// A short, human-readable, description of the type and default value.
+    pub(crate) fn summary(&self) -> String {
+        match self.kind {
+            SettingType::Bool => format!("a boolean, by default `{}`", self.default_bool()),
+            SettingType::Array if self.default.is_empty() => "a list, by default empty".to_string(),
+            SettingType::Array => format!(
+                "a list, by default {}",
+                self.default_array()
+                    .iter()
+                    .map(|v| format!("`{v}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            SettingType::String if self.default.is_empty() => {
+                "a string, by default empty".to_string()
+            }
+            SettingType::String => format!("a string, by default `{}`", self.default),
+            SettingType::Int => format!("an integer, by default `{}`", self.default),
+        }
+    }
+
+    /// The bullet documenting this setting in the plugin file's header.
+    fn header_doc(&self, plugin_name: &str) -> String {
+        let description = self
+            .description()
+            .map(|d| format!("{}. ", d.trim_end_matches('.')))
+            .unwrap_or_default();
+        format!(
+            "# * **zstyle ':plugin:{plugin_name}' {}**: {description}{}, see `{}`.\n",
+            self.name,
+            capitalize(&self.summary()),
+            self.function_name(plugin_name)
+        )
+    }
+
+    /// Shellspec examples for the default and an overridden value.
+    fn spec_cases(&self, plugin_name: &str) -> String {
+        let (context, function) = (
+            format!(":plugin:{plugin_name}"),
+            self.function_name(plugin_name),
+        );
+        let mut spec = String::new();
+        let _ = writeln!(spec);
+        let _ = writeln!(spec, "Describe '{function}'");
+        let _ = writeln!(spec, "  Include ./{plugin_name}.plugin.zsh");
+        let _ = writeln!(
+            spec,
+            "  cleanup() {{ zstyle -d '{context}' {}; }}",
+            self.name
+        );
+        let _ = writeln!(spec, "  AfterEach 'cleanup'");
+        let _ = writeln!(spec);
+        let (override_value, expected) = match self.kind {
+            SettingType::Bool => {
+                let value = if self.default_bool() { "no" } else { "yes" };
+                (value.to_string(), None)
+            }
+            SettingType::String => ("'overridden'".to_string(), Some("overridden".to_string())),
+            SettingType::Int => {
+                let value = self.default.parse::<i64>().unwrap_or_default() + 1;
+                (value.to_string(), Some(value.to_string()))
+            }
+            SettingType::Array => ("'one' 'two'".to_string(), Some("one two".to_string())),
+        };
+        let default_expected = match self.kind {
+            SettingType::Array => self.default_array().join(" "),
+            _ => self.default.clone(),
+        };
+
+        let _ = writeln!(spec, "  It 'uses the default value'");
+        let _ = writeln!(spec, "    When call {function}");
+        match self.kind {
+            SettingType::Bool if self.default_bool() => {
+                let _ = writeln!(spec, "    The status should be success");
+            }
+            SettingType::Bool => {
+                let _ = writeln!(spec, "    The status should be failure");
+            }
+            _ => {
+                let _ = writeln!(spec, "    The output should eq '{default_expected}'");
+            }
+        }
+        let _ = writeln!(spec, "  End");
+        let _ = writeln!(spec);
+        let _ = writeln!(spec, "  It 'uses the value set with zstyle'");
+        let _ = writeln!(
+            spec,
+            "    zstyle '{context}' {} {override_value}",
+            self.name
+        );
+        let _ = writeln!(spec, "    When call {function}");
+        match expected {
+            Some(expected) => {
+                let _ = writeln!(spec, "    The output should eq '{expected}'");
+            }
+            None if self.default_bool() => {
+                let _ = writeln!(spec, "    The status should be failure");
+            }
+            None => {
+                let _ = writeln!(spec, "    The status should be success");
+            }
+        }
+        let _ = writeln!(spec, "  End");
+        let _ = writeln!(spec, "End");
+        spec
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Insert `line` after the last bullet of the "Public Variables" section of the
+/// plugin file's header, returning `None` if there is no such section.
+fn document_setting(source: &str, line: &str) -> Option<String> {
+    const HEADING: &str = "# ### Public Variables\n";
+    let mut insert_at = source.find(HEADING)? + HEADING.len();
+    if source[insert_at..].starts_with("#\n") {
+        insert_at += 2;
+    }
+    for current in source[insert_at..].split_inclusive('\n') {
+        if !(current.starts_with("# * ") || current.starts_with("#   ")) {
+            break;
+        }
+        insert_at += current.len();
+    }
+    Some(format!(
+        "{}{line}{}",
+        &source[..insert_at],
+        &source[insert_at..]
+    ))
+}
+
+fn bool_value(value: &str) -> Option<bool> {
+    match value {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn invalid_default(name: &Name, kind: SettingType, value: &str) -> Error {
+    error!("Setting::new; default {value:?} of setting {name} is not a valid {kind}");
+    Error::InvalidSettingDefault {
+        name: name.to_string(),
+        kind: kind.to_string(),
+        value: value.to_string(),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(s: &str) -> Name {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn check_setting_defaults() {
+        let setting = Setting::new(&name("enabled"), SettingType::Bool, Some("yes"), None).unwrap();
+        assert!(setting.default_bool());
+        let setting = Setting::new(&name("depth"), SettingType::Int, None, None).unwrap();
+        assert_eq!(setting.default(), "0");
+        assert!(Setting::new(&name("depth"), SettingType::Int, Some("deep"), None).is_err());
+        assert!(Setting::new(&name("enabled"), SettingType::Bool, Some("maybe"), None).is_err());
+        let setting = Setting::new(&name("paths"), SettingType::Array, Some("a, b"), None).unwrap();
+        assert_eq!(setting.default_array(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn document_in_public_variables() {
+        let source = "#\n# ### Public Variables\n#\n# * **X**: x.\n#\n\ncode\n";
+        assert_eq!(
+            document_setting(source, "# * **Y**: y.\n").unwrap(),
+            "#\n# ### Public Variables\n#\n# * **X**: x.\n# * **Y**: y.\n#\n\ncode\n"
+        );
+        assert_eq!(document_setting("code\n", "# * **Y**: y.\n"), None);
+    }
+}
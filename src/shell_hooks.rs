@@ -0,0 +1,163 @@
+//! Zsh hook functions registered by a plugin, and removed when it is unloaded.
+//!
+//! For each hook a named function, `NAME_HOOK_hook`, is added to the plugin file
+//! between the `hook-functions` markers for the author to complete. The
+//! `hooks-add` block of the plugin's `_plugin_init` function registers it with
+//! `add-zsh-hook`, and for plain plugins records it in the `_HOOKS` key of the
+//! plugin hash, while the `hooks-remove` block of `_plugin_unload` removes it
+//! again with `add-zsh-hook -d`. See the "Hook Functions" section of the Zsh
+//! manual for when each hook is called.
+
+use crate::{
+    blocks::{
+        B_HOOK_FUNCTIONS, B_HOOKS_ADD, B_HOOKS_REMOVE, append_to_block, block_content,
+        hook_functions_block, hooks_add_block, hooks_remove_block,
+    },
+    error::Error,
+    manifest::update_manifest,
+    plugin::Plugin,
+    readme::update_readme,
+};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs::read_to_string,
+    process::ExitCode,
+};
+use tracing::trace;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// A Zsh hook that a plugin function may be registered with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ShellHook {
+    /// Called when the current working directory changes.
+    Chpwd,
+
+    /// Called every `$PERIOD` seconds, just before a prompt.
+    Periodic,
+
+    /// Called before each prompt.
+    Precmd,
+
+    /// Called when a command has been read and is about to be executed.
+    Preexec,
+
+    /// Called when a history line has been read, before it is executed.
+    Zshaddhistory,
+
+    /// Called when the shell exits.
+    Zshexit,
+
+    /// Called to convert between directory names and dynamic named directories.
+    #[value(name = "zsh_directory_name")]
+    #[serde(rename = "zsh_directory_name")]
+    ZshDirectoryName,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Add a hook function for each of `hooks` to the plugin, registered by its init
+/// function and removed by its unload function, ignoring any hook the plugin
+/// already has a function for.
+pub(crate) fn add_hooks(plugin: &Plugin, hooks: &[ShellHook]) -> Result<ExitCode, Error> {
+    trace!("add_hooks => plugin: {plugin:?}, hooks: {hooks:?}");
+    let plugin_file = plugin.plugin_file();
+    let source = read_to_string(&plugin_file)?;
+    let existing = block_content(&source, B_HOOK_FUNCTIONS).unwrap_or_default();
+    let name = plugin.name();
+
+    let mut added: Vec<ShellHook> = Vec::default();
+    for hook in hooks {
+        if existing.contains(&format!("{}() {{", hook.function_name(name))) || added.contains(hook)
+        {
+            eprintln!("The plugin already has a function for the hook '{hook}', ignoring.");
+        } else {
+            added.push(*hook);
+        }
+    }
+
+    let (var, zplugins) = (plugin.var(), plugin.uses_zplugins());
+    append_to_block(
+        &plugin_file,
+        B_HOOK_FUNCTIONS,
+        &hook_functions_block(name, zplugins, &added),
+    )?;
+    print!(".");
+    append_to_block(
+        &plugin_file,
+        B_HOOKS_ADD,
+        &hooks_add_block(name, &var, zplugins, &added),
+    )?;
+    print!(".");
+    append_to_block(
+        &plugin_file,
+        B_HOOKS_REMOVE,
+        &hooks_remove_block(name, &added),
+    )?;
+    update_manifest(plugin)?;
+    update_readme(plugin)?;
+    println!(". Done");
+
+    for hook in &added {
+        println!("Added hook function: {}", hook.function_name(name));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ShellHook {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Chpwd => "chpwd",
+                Self::Periodic => "periodic",
+                Self::Precmd => "precmd",
+                Self::Preexec => "preexec",
+                Self::Zshaddhistory => "zshaddhistory",
+                Self::Zshexit => "zshexit",
+                Self::ZshDirectoryName => "zsh_directory_name",
+            }
+        )
+    }
+}
+
+impl ShellHook {
+    /// The name of the plugin's function registered with this hook.
+    pub(crate) fn function_name(&self, plugin_name: &str) -> String {
+        format!("{plugin_name}_{self}_hook")
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hook_names_match_zsh() {
+        for hook in ShellHook::value_variants() {
+            let value = hook.to_possible_value().unwrap();
+            assert_eq!(value.get_name(), hook.to_string());
+        }
+        assert_eq!(
+            ShellHook::ZshDirectoryName.function_name("demo"),
+            "demo_zsh_directory_name_hook"
+        );
+    }
+}
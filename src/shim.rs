@@ -0,0 +1,350 @@
+//! Ports of a plugin's public commands to the Fish and POSIX sh shells.
+//!
+//! The `export shim` command exposes the parts of a plugin's public surface that
+//! do not depend on Zsh to other shells:
+//!
+//! * the `bin` directory is added to `PATH`, so public functions implemented as
+//!   executables of the same name need no port;
+//! * a public function whose body is a single simple command, for example
+//!   `command git log --oneline "$@"`, is ported as a function running that
+//!   command with the same arguments; and
+//! * a tracked alias whose value is a simple command is ported as an alias.
+//!
+//! Anything else, such as functions using Zsh-only syntax, pipelines or command
+//! substitution, is reported as not ported. For Fish a `conf.d/NAME.fish` file,
+//! and a `functions/FUNCTION.fish` file per function, are written to the output
+//! directory, for sh a single `NAME.sh` file to source. Both find the plugin's
+//! `bin` directory through the variable `VAR_PLUGIN_DIR`, set to the plugin's
+//! current directory unless it is already set.
+
+use crate::{
+    check::zsh_only_syntax,
+    doc::{PluginDocs, function_body, shell_words},
+    error::Error,
+    manifest::Manifest,
+    package::P_DIST_DIR,
+    plugin::Plugin,
+    templates::P_BIN_DIR,
+    version::current_version,
+};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs::{create_dir_all, read_dir, read_to_string, write},
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    process::ExitCode,
+};
+use tera::{Context, Tera};
+use tracing::trace;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// The shell a plugin's public commands are ported to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ShimShell {
+    /// A Fish 'conf.d' file and a 'functions' directory.
+    Fish,
+
+    /// A single POSIX sh file to source.
+    Sh,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Write a port of the public functions and aliases of `plugin` to `shell` into
+/// `output_dir`, which defaults to the plugin's `dist/SHELL` directory.
+pub(crate) fn export_shim(
+    plugin: &Plugin,
+    shell: ShimShell,
+    output_dir: Option<&Path>,
+) -> Result<ExitCode, Error> {
+    trace!("export_shim => plugin: {plugin:?}, shell: {shell}, output_dir: {output_dir:?}");
+    let root = plugin.root();
+    let output_dir = output_dir
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| root.join(P_DIST_DIR).join(shell.to_string()));
+    let manifest = Manifest::current(plugin)?;
+    let docs = PluginDocs::load(plugin)?;
+    let quote: fn(&str) -> String = match shell {
+        ShimShell::Fish => fish_quote,
+        ShimShell::Sh => sh_quote,
+    };
+
+    let mut sources = vec![read_to_string(plugin.plugin_file())?];
+    for module in plugin.module_files()? {
+        sources.push(read_to_string(module)?);
+    }
+    let plugin_fns = docs
+        .functions()
+        .map(|f| f.name().to_string())
+        .collect::<Vec<_>>();
+    let wraps_plugin_fn = |command: &str| {
+        shell_words(command)
+            .first()
+            .is_some_and(|word| word.starts_with('.') || plugin_fns.contains(word))
+    };
+
+    let mut functions = Vec::default();
+    let mut not_ported = Vec::default();
+    for name in &manifest.provides.functions {
+        if is_executable(&root.join(P_BIN_DIR).join(name)) {
+            continue;
+        }
+        let body = match read_to_string(plugin.functions_dir().join(name)) {
+            Ok(content) => Some(content.lines().map(str::to_string).collect()),
+            Err(_) => sources.iter().find_map(|s| function_body(s, name)),
+        };
+        match body
+            .as_deref()
+            .and_then(wrapped_command)
+            .filter(|command| !wraps_plugin_fn(command))
+        {
+            Some(command) => functions.push(ShimFunction {
+                name: name.clone(),
+                command: match shell {
+                    ShimShell::Fish => command.replace("\"$@\"", "$argv").replace("$@", "$argv"),
+                    ShimShell::Sh => command,
+                },
+                description: docs
+                    .functions()
+                    .find(|f| f.name() == name)
+                    .and_then(|f| f.description())
+                    .and_then(|d| d.lines().next())
+                    .map(|d| match shell {
+                        // Only Fish quotes the description, sh shows it as a comment.
+                        ShimShell::Fish => fish_quote(d),
+                        ShimShell::Sh => d.to_string(),
+                    }),
+            }),
+            None => not_ported.push(format!("function {name}")),
+        }
+    }
+
+    let ported = |command: &str| {
+        shell_words(command).first().is_none_or(|word| {
+            !plugin_fns.contains(word) || functions.iter().any(|f| &f.name == word)
+        })
+    };
+    let mut aliases = Vec::default();
+    for alias in docs.aliases() {
+        match simple_command(alias.value()).filter(|command| ported(command)) {
+            Some(command) => aliases.push(ShimAlias {
+                name: alias.name().to_string(),
+                value: quote(&command),
+            }),
+            None => not_ported.push(format!("alias {}", alias.name())),
+        }
+    }
+
+    let mut ctx = Context::new();
+    ctx.insert(V_PLUGIN_DISPLAY_NAME, plugin.display_name());
+    ctx.insert(V_VERSION, &current_version(plugin)?.to_string());
+    ctx.insert(V_DESCRIPTION, &manifest.plugin.description);
+    ctx.insert(V_DIR_VAR, &format!("{}_PLUGIN_DIR", plugin.var()));
+    ctx.insert(
+        V_PLUGIN_DIR,
+        &quote(&root.canonicalize()?.to_string_lossy()),
+    );
+    ctx.insert(V_HAS_BIN, &has_files(&root.join(P_BIN_DIR)));
+    ctx.insert(V_ALIASES, &aliases);
+
+    let mut tera = Tera::default();
+    match shell {
+        ShimShell::Fish => {
+            let functions_dir = output_dir.join(P_FISH_FUNCTIONS_DIR);
+            create_dir_all(output_dir.join(P_FISH_CONF_DIR))?;
+            create_dir_all(&functions_dir)?;
+            render(
+                &mut tera,
+                T_FISH_CONF,
+                &ctx,
+                &output_dir
+                    .join(P_FISH_CONF_DIR)
+                    .join(format!("{}.fish", plugin.name())),
+            )?;
+            for function in &functions {
+                ctx.insert(V_FUNCTION, function);
+                render(
+                    &mut tera,
+                    T_FISH_FUNCTION,
+                    &ctx,
+                    &functions_dir.join(format!("{}.fish", function.name)),
+                )?;
+            }
+        }
+        ShimShell::Sh => {
+            create_dir_all(&output_dir)?;
+            ctx.insert(V_FUNCTIONS, &functions);
+            render(
+                &mut tera,
+                T_SH_SHIM,
+                &ctx,
+                &output_dir.join(format!("{}.sh", plugin.name())),
+            )?;
+        }
+    }
+
+    if !not_ported.is_empty() {
+        eprintln!("Not ported, as neither a 'bin' executable nor a simple command:");
+        let count = not_ported.len();
+        for (i, item) in not_ported.iter().enumerate() {
+            let branch = if i + 1 == count { "└─" } else { "├─" };
+            eprintln!("{branch} {item}");
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, Serialize)]
+struct ShimFunction {
+    name: String,
+    command: String,
+    description: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct ShimAlias {
+    name: String,
+    value: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ShimShell {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Fish => "fish",
+                Self::Sh => "sh",
+            }
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+const T_FISH_CONF: &str = include_str!("templates/export/conf.fish");
+const T_FISH_FUNCTION: &str = include_str!("templates/export/function.fish");
+const T_SH_SHIM: &str = include_str!("templates/export/shim.sh");
+
+const P_FISH_CONF_DIR: &str = "conf.d";
+const P_FISH_FUNCTIONS_DIR: &str = "functions";
+
+const V_ALIASES: &str = "aliases";
+const V_DESCRIPTION: &str = "description";
+const V_DIR_VAR: &str = "dir_var";
+const V_FUNCTION: &str = "function";
+const V_FUNCTIONS: &str = "functions";
+const V_HAS_BIN: &str = "has_bin";
+const V_PLUGIN_DIR: &str = "plugin_dir";
+const V_PLUGIN_DISPLAY_NAME: &str = "plugin_display_name";
+const V_VERSION: &str = "version";
+
+/// The characters, outside of `"$@"`, that make a command more than simple.
+const NOT_SIMPLE: &[char] = &[
+    '$', '`', '|', '&', ';', '<', '>', '(', ')', '{', '}', '*', '?', '[', '\\', '~', '!',
+];
+
+fn render(tera: &mut Tera, template: &str, ctx: &Context, path: &Path) -> Result<(), Error> {
+    write(path, tera.render_str(template, ctx)?)?;
+    println!("Wrote: {}", path.display());
+    Ok(())
+}
+
+/// The single command a function body runs, ignoring comments and `emulate`.
+fn wrapped_command(body: &[String]) -> Option<String> {
+    let mut lines = body.iter().map(|line| line.trim()).filter(|line| {
+        !(line.is_empty()
+            || line.starts_with('#')
+            || *line == "{"
+            || line.starts_with("builtin emulate ")
+            || line.starts_with("emulate "))
+    });
+    match (lines.next(), lines.next()) {
+        (Some(line), None) => simple_command(line),
+        _ => None,
+    }
+}
+
+/// `command` without a trailing `;`, if it runs a single program with literal
+/// arguments and, optionally, `"$@"`.
+fn simple_command(command: &str) -> Option<String> {
+    let command = command.trim().trim_end_matches(';').trim_end();
+    let rest = command.replace("\"$@\"", "").replace("$@", "");
+    (!command.is_empty() && !rest.contains(NOT_SIMPLE) && zsh_only_syntax(command).is_empty())
+        .then(|| command.to_string())
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+fn has_files(dir: &Path) -> bool {
+    read_dir(dir).is_ok_and(|mut entries| {
+        entries.any(|entry| entry.is_ok_and(|e| !e.file_name().to_string_lossy().starts_with('.')))
+    })
+}
+
+/// The content of a single-quoted Fish string.
+fn fish_quote(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+/// The content of a single-quoted sh string.
+fn sh_quote(value: &str) -> String {
+    value.replace('\'', "'\\''")
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_wrapped_commands() {
+        let body = |lines: &[&str]| lines.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            wrapped_command(&body(&[
+                "    builtin emulate -L zsh",
+                "",
+                "    command git log --oneline \"$@\"",
+            ])),
+            Some("command git log --oneline \"$@\"".to_string())
+        );
+        assert_eq!(
+            wrapped_command(&body(&["kubectl get pods;"])),
+            Some("kubectl get pods".to_string())
+        );
+        assert_eq!(wrapped_command(&body(&["ls | less"])), None);
+        assert_eq!(wrapped_command(&body(&["cd \"${1:h}\""])), None);
+        assert_eq!(wrapped_command(&body(&["local x=1", "echo"])), None);
+        assert_eq!(simple_command("print -P \"$@\""), None);
+    }
+
+    #[test]
+    fn quote_strings() {
+        assert_eq!(fish_quote(r"it's a \ test"), r"it\'s a \\ test");
+        assert_eq!(sh_quote("it's"), r"it'\''s");
+    }
+}
@@ -1,11 +1,31 @@
-use crate::{cli::InitCommand, error::Error};
-use git2::Repository;
+use crate::{
+    blocks::{
+        B_MODULES, block_content, dependencies_block, env_restore_block, env_save_block,
+        hook_functions_block, hooks_add_block, hooks_remove_block, modules_block,
+        requirements_block, update_block,
+    },
+    cli::{CiTarget, InitCommand},
+    environment::EnvVar,
+    error::Error,
+    manifest::{Manifest, update_manifest},
+    name::Name,
+    plugin::Plugin,
+    readme::update_readme,
+    repository::RemoteRepository,
+    requirements::CommandRequirement,
+    shell_hooks::ShellHook,
+};
+use chrono::{Datelike, Local};
+use flat_error::FlatError;
+use git2::{IndexAddOption, Repository, RepositoryInitOptions, Signature};
+use serde::de::DeserializeOwned;
 use std::{
-    fs::{create_dir_all, write},
+    env::var,
+    fs::{create_dir_all, read_to_string, set_permissions, write},
     path::{Path, PathBuf},
     process::ExitCode,
 };
-use tera::{Context, Tera};
+use tera::{Context, Tera, from_value};
 use tracing::{error, trace};
 
 // ------------------------------------------------------------------------------------------------
@@ -20,6 +40,28 @@ fn ctx_get_str<'a>(ctx: &'a Context, key: &str) -> Result<&'a str, Error> {
         })
 }
 
+fn ctx_get_str_list(ctx: &Context, key: &str) -> Result<Vec<String>, Error> {
+    ctx.get(key)
+        .and_then(|v| v.as_array())
+        .and_then(|vs| {
+            vs.iter()
+                .map(|v| v.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+        })
+        .ok_or_else(|| Error::Unknown {
+            message: format!("Missing or invalid context key: {key}"),
+        })
+}
+
+fn ctx_get_list<T: DeserializeOwned>(ctx: &Context, key: &str) -> Result<Vec<T>, Error> {
+    ctx.get(key)
+        .cloned()
+        .and_then(|v| from_value(v).ok())
+        .ok_or_else(|| Error::Unknown {
+            message: format!("Missing or invalid context key: {key}"),
+        })
+}
+
 fn ctx_get_bool(ctx: &Context, key: &str) -> Result<bool, Error> {
     ctx.get(key)
         .and_then(|v| v.as_bool())
@@ -32,33 +74,72 @@ fn ctx_get_bool(ctx: &Context, key: &str) -> Result<bool, Error> {
 // Public Functions
 // ------------------------------------------------------------------------------------------------
 
-const V_GITHUB_USER: &str = "github_user";
+const V_AUTHOR: &str = "author";
+const V_AUTHOR_EMAIL: &str = "author_email";
+const V_CI: &str = "ci";
+const V_COPYRIGHT_YEAR: &str = "copyright_year";
+const V_DEPENDENCIES: &str = "dependencies";
+const V_DEPENDENCIES_BLOCK: &str = "dependencies_block";
+const V_ENV_RESTORE_BLOCK: &str = "env_restore_block";
+const V_ENV_SAVE_BLOCK: &str = "env_save_block";
+const V_ENV_VARS: &str = "env_vars";
+const V_GIT_BRANCH: &str = "git_branch";
+const V_HOOK_FUNCTIONS_BLOCK: &str = "hook_functions_block";
+const V_HOOKS: &str = "hooks";
+const V_HOOKS_ADD_BLOCK: &str = "hooks_add_block";
+const V_HOOKS_REMOVE_BLOCK: &str = "hooks_remove_block";
+const V_LICENSE: &str = "license";
+const V_MODULE_FN_NAME: &str = "module_fn_name";
+const V_MODULE_NAME: &str = "module_name";
+const V_MODULES: &str = "modules";
+const V_MODULES_BLOCK: &str = "modules_block";
 const V_PLUGIN_DISPLAY_NAME: &str = "plugin_display_name";
 const V_PLUGIN_NAME: &str = "plugin_name";
 const V_PLUGIN_VAR: &str = "plugin_var";
+const V_PLUGIN_VERSION: &str = "plugin_version";
+const V_REQUIREMENTS: &str = "requirements";
+const V_REQUIREMENTS_BLOCK: &str = "requirements_block";
+const V_REPOSITORY_CLONE_URL: &str = "repository_clone_url";
+const V_REPOSITORY_HOMEPAGE: &str = "repository_homepage";
+const V_REPOSITORY_HOST: &str = "repository_host";
+const V_REPOSITORY_NAME: &str = "repository_name";
+const V_REPOSITORY_OWNER: &str = "repository_owner";
+const V_REPOSITORY_URL: &str = "repository_url";
 const V_SHORT_DESCRIPTION: &str = "short_description";
 
 const O_INCLUDE_ALIASES: &str = "include_aliases";
 const O_INCLUDE_BASH_WRAPPER: &str = "include_bash_wrapper";
 const O_INCLUDE_BIN_DIR: &str = "include_bin_dir";
+const O_INCLUDE_CI: &str = "include_ci";
 const O_INCLUDE_FUNCTIONS_DIR: &str = "include_functions_dir";
+const O_INCLUDE_GIT_COMMIT: &str = "include_git_commit";
+const O_INCLUDE_GIT_HOOKS: &str = "include_git_hooks";
+const O_INCLUDE_GIT_PRE_PUSH: &str = "include_git_pre_push";
 const O_INCLUDE_GIT_INIT: &str = "include_git_init";
-const O_INCLUDE_GITHUB_DIR: &str = "include_github_dir";
+const O_INCLUDE_GIT_REMOTE: &str = "include_git_remote";
+const O_INCLUDE_MODULES: &str = "include_modules";
 const O_INCLUDE_README: &str = "include_readme";
 const O_INCLUDE_SHELL_CHECK: &str = "include_shell_check";
 const O_INCLUDE_SHELL_DOC: &str = "include_shell_doc";
 const O_INCLUDE_SHELL_SPEC: &str = "include_shell_spec";
 const O_USE_PLAIN_PLUGINS: &str = "use_plain_plugins";
 
-const P_BIN_DIR: &str = "bin";
-const P_DOC_DIR: &str = "doc";
-const P_DOT_GITIGNORE: &str = ".gitignore";
+pub(crate) const P_BIN_DIR: &str = "bin";
+pub(crate) const P_DOC_DIR: &str = "doc";
+pub(crate) const P_DOT_GITIGNORE: &str = ".gitignore";
+pub(crate) const P_DOT_GITLAB_CI: &str = ".gitlab-ci.yml";
 const P_DOT_KEEP: &str = ".gitkeep";
-const P_FUNCTIONS_DIR: &str = "functions";
-const P_GITHUB_DIR: &str = ".github";
-const P_MAKEFILE: &str = "Makefile";
-const P_MKDOC: &str = "mkdoc.zsh";
-const P_README: &str = "README.md";
+pub(crate) const P_FORGEJO_DIR: &str = ".forgejo";
+pub(crate) const P_FUNCTIONS_DIR: &str = "functions";
+const P_GIT_DIR: &str = ".git";
+pub(crate) const P_GIT_HOOKS_DIR: &str = ".githooks";
+pub(crate) const P_GITHUB_DIR: &str = ".github";
+pub(crate) const P_MAKEFILE: &str = "Makefile";
+pub(crate) const P_MKDOC: &str = "mkdoc.zsh";
+pub(crate) const P_MODULES_DIR: &str = "modules";
+const P_PRE_COMMIT: &str = "pre-commit";
+pub(crate) const P_PRE_PUSH: &str = "pre-push";
+pub(crate) const P_README: &str = "README.md";
 const P_SHELL_YML: &str = "shell.yml";
 const P_WORKFLOWS_DIR: &str = "workflows";
 
@@ -66,8 +147,13 @@ const T_BIN_DIR_KEEP: &str = include_str!("templates/bin/.keep");
 const T_MKDOC: &str = include_str!("templates/mkdoc.zsh");
 const T_FUNCTIONS_EXAMPLE: &str = include_str!("templates/functions/name_example");
 const T_GIT_IGNORE: &str = include_str!("templates/.gitignore");
+const T_FORGEJO_WORKFLOW_SHELL: &str = include_str!("templates/.forgejo/workflows/shell.yml");
 const T_GITHUB_WORFLOW_SHELL: &str = include_str!("templates/.github/workflows/shell.yml");
+const T_GITLAB_CI: &str = include_str!("templates/.gitlab-ci.yml");
 const T_MAKEFILE: &str = include_str!("templates/Makefile");
+const T_MODULE: &str = include_str!("templates/modules/name.zsh");
+const T_PRE_COMMIT_HOOK: &str = include_str!("templates/.githooks/pre-commit");
+const T_PRE_PUSH_HOOK: &str = include_str!("templates/.githooks/pre-push");
 const T_PLUGIN_SOURCE: &str = include_str!("templates/name.plugin.zsh");
 const T_PLUGIN_SOURCE_ZPLUGINS: &str = include_str!("templates/name.zplugins.zsh");
 const T_PLUGIN_WRAPPER: &str = include_str!("templates/name.bash");
@@ -82,16 +168,17 @@ macro_rules! report_progress {
     };
 }
 
-pub(crate) fn init_new_plugin(ctx: Context, force: bool) -> Result<ExitCode, Error> {
+pub(crate) fn init_new_plugin(mut ctx: Context, force: bool) -> Result<ExitCode, Error> {
     trace!("init_new_plugin => ctx: {ctx:?}, force: {force}");
     let mut tera = Tera::default();
+    insert_blocks(&mut ctx)?;
     let plugin_name: &str = ctx_get_str(&ctx, V_PLUGIN_NAME)?;
 
-    let target_root = PathBuf::from(&format!("zsh-{plugin_name}-plugin"));
+    let target_root = target_root(plugin_name);
     make_directory(&target_root, force)?;
 
     if ctx_get_bool(&ctx, O_INCLUDE_GIT_INIT)? {
-        make_repository(&target_root, force)?;
+        make_repository(&target_root, &ctx, force)?;
         render_template(
             &mut tera,
             &ctx,
@@ -101,18 +188,34 @@ pub(crate) fn init_new_plugin(ctx: Context, force: bool) -> Result<ExitCode, Err
         )?;
     }
 
-    if ctx_get_bool(&ctx, O_INCLUDE_GITHUB_DIR)? {
-        let github = target_root.join(P_GITHUB_DIR);
-        make_directory(&github, force)?;
-        let workflows = github.join(P_WORKFLOWS_DIR);
-        make_directory(&workflows, force)?;
-        render_template(
-            &mut tera,
-            &ctx,
-            T_GITHUB_WORFLOW_SHELL,
-            &workflows.join(P_SHELL_YML),
-            force,
-        )?;
+    if ctx_get_bool(&ctx, O_INCLUDE_CI)? {
+        match ctx_get_str(&ctx, V_CI)? {
+            "gitlab" => render_template(
+                &mut tera,
+                &ctx,
+                T_GITLAB_CI,
+                &target_root.join(P_DOT_GITLAB_CI),
+                force,
+            )?,
+            ci => {
+                let (forge_dir, template) = if ci == "forgejo" {
+                    (P_FORGEJO_DIR, T_FORGEJO_WORKFLOW_SHELL)
+                } else {
+                    (P_GITHUB_DIR, T_GITHUB_WORFLOW_SHELL)
+                };
+                let forge = target_root.join(forge_dir);
+                make_directory(&forge, force)?;
+                let workflows = forge.join(P_WORKFLOWS_DIR);
+                make_directory(&workflows, force)?;
+                render_template(
+                    &mut tera,
+                    &ctx,
+                    template,
+                    &workflows.join(P_SHELL_YML),
+                    force,
+                )?;
+            }
+        }
     }
 
     if ctx_get_bool(&ctx, O_INCLUDE_BIN_DIR)? {
@@ -139,6 +242,14 @@ pub(crate) fn init_new_plugin(ctx: Context, force: bool) -> Result<ExitCode, Err
         )?;
     }
 
+    if ctx_get_bool(&ctx, O_INCLUDE_MODULES)? {
+        let modules = target_root.join(P_MODULES_DIR);
+        make_directory(&modules, force)?;
+        for module in ctx_get_str_list(&ctx, V_MODULES)? {
+            render_module(&mut tera, &ctx, &modules, &module, force)?;
+        }
+    }
+
     if ctx_get_bool(&ctx, O_INCLUDE_SHELL_CHECK)?
         || ctx_get_bool(&ctx, O_INCLUDE_SHELL_DOC)?
         || ctx_get_bool(&ctx, O_INCLUDE_SHELL_SPEC)?
@@ -191,6 +302,105 @@ pub(crate) fn init_new_plugin(ctx: Context, force: bool) -> Result<ExitCode, Err
         render_template(&mut tera, &ctx, T_MKDOC, &target_root.join(P_MKDOC), force)?;
     }
 
+    if ctx_get_bool(&ctx, O_INCLUDE_GIT_HOOKS)? {
+        let hooks = target_root.join(P_GIT_HOOKS_DIR);
+        make_directory(&hooks, force)?;
+        let pre_push = ctx_get_bool(&ctx, O_INCLUDE_GIT_PRE_PUSH)?;
+        render_hooks(&mut tera, &ctx, &hooks, pre_push, force)?;
+    }
+
+    let plugin = Plugin::open(&target_root)?;
+    let mut manifest = Manifest::detect(&plugin)?;
+    manifest.dependencies.plugins = ctx_get_str_list(&ctx, V_DEPENDENCIES)?;
+    manifest.dependencies.commands = ctx_get_list(&ctx, V_REQUIREMENTS)?;
+    manifest.save(&plugin)?;
+    report_progress!();
+
+    if ctx_get_bool(&ctx, O_INCLUDE_README)? {
+        update_readme(&plugin)?;
+        report_progress!();
+    }
+
+    if ctx_get_bool(&ctx, O_INCLUDE_GIT_INIT)? && ctx_get_bool(&ctx, O_INCLUDE_GIT_COMMIT)? {
+        make_initial_commit(&target_root, &ctx)?;
+    }
+
+    report_progress!(done);
+
+    Ok(ExitCode::SUCCESS)
+}
+
+pub(crate) fn add_new_module(
+    plugin: &Plugin,
+    module: &Name,
+    force: bool,
+) -> Result<ExitCode, Error> {
+    trace!("add_new_module => plugin: {plugin:?}, module: {module}, force: {force}");
+    let mut tera = Tera::default();
+    let ctx = Context::from(plugin);
+
+    let modules = plugin.modules_dir();
+    if !modules.is_dir() {
+        make_directory(&modules, force)?;
+    }
+    render_module(&mut tera, &ctx, &modules, module.as_ref(), force)?;
+
+    // Plugins created without modules have an empty block, filled in by the first module.
+    let plugin_file = plugin.plugin_file();
+    let sources_modules =
+        match block_content(&read_to_string(&plugin_file)?, B_MODULES).map(str::trim) {
+            Some("") => {
+                update_block(
+                    &plugin_file,
+                    B_MODULES,
+                    &modules_block(plugin.name(), &plugin.var(), plugin.uses_zplugins()),
+                )?;
+                true
+            }
+            Some(_) => true,
+            None => read_to_string(&plugin_file)?.contains("/modules/"),
+        };
+    update_readme(plugin)?;
+    update_manifest(plugin)?;
+
+    report_progress!(done);
+
+    if !sources_modules {
+        eprintln!(
+            r#"The plugin file does not appear to source modules.
+└─ Help: Add code to source the files in '{P_MODULES_DIR}' to the function '{}_plugin_init'."#,
+            plugin.name()
+        );
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+pub(crate) fn install_hooks(
+    plugin: &Plugin,
+    pre_push: bool,
+    force: bool,
+) -> Result<ExitCode, Error> {
+    trace!("install_hooks => plugin: {plugin:?}, pre_push: {pre_push}, force: {force}");
+    let mut tera = Tera::default();
+    let ctx = Context::from(plugin);
+
+    let hooks = plugin.root().join(P_GIT_HOOKS_DIR);
+    if !hooks.is_dir() {
+        make_directory(&hooks, force)?;
+    }
+    render_hooks(&mut tera, &ctx, &hooks, pre_push, force)?;
+
+    Repository::open(plugin.root())
+        .and_then(|repository| set_hooks_path(&repository))
+        .map_err(|e| {
+            error!("Error configuring hooks in Git repository, error: {e}");
+            Error::Git {
+                source: FlatError::from_any(&e),
+            }
+        })?;
+    update_manifest(plugin)?;
+
     report_progress!(done);
 
     Ok(ExitCode::SUCCESS)
@@ -200,18 +410,129 @@ pub(crate) fn init_new_plugin(ctx: Context, force: bool) -> Result<ExitCode, Err
 // Private Functions
 // ------------------------------------------------------------------------------------------------
 
-fn make_repository(path: &Path, force: bool) -> Result<(), Error> {
+/// The directory created for a new plugin, relative to the current directory.
+fn target_root(plugin_name: &str) -> PathBuf {
+    PathBuf::from(format!("zsh-{plugin_name}-plugin"))
+}
+
+/// Render the content of each generated block of the plugin file into `ctx`.
+fn insert_blocks(ctx: &mut Context) -> Result<(), Error> {
+    let dependencies = ctx_get_str_list(ctx, V_DEPENDENCIES)?;
+    let requirements: Vec<CommandRequirement> = ctx_get_list(ctx, V_REQUIREMENTS)?;
+    let env_vars: Vec<EnvVar> = ctx_get_list(ctx, V_ENV_VARS)?;
+    let hooks: Vec<ShellHook> = ctx_get_list(ctx, V_HOOKS)?;
+    let (plugin_name, plugin_var, display_name) = (
+        ctx_get_str(ctx, V_PLUGIN_NAME)?.to_string(),
+        ctx_get_str(ctx, V_PLUGIN_VAR)?.to_string(),
+        ctx_get_str(ctx, V_PLUGIN_DISPLAY_NAME)?.to_string(),
+    );
+    let uses_zplugins = !ctx_get_bool(ctx, O_USE_PLAIN_PLUGINS)?;
+    ctx.insert(
+        V_DEPENDENCIES_BLOCK,
+        &dependencies_block(&plugin_name, &display_name, uses_zplugins, &dependencies),
+    );
+    ctx.insert(
+        V_REQUIREMENTS_BLOCK,
+        &requirements_block(&display_name, &requirements),
+    );
+    ctx.insert(
+        V_MODULES_BLOCK,
+        &if ctx_get_bool(ctx, O_INCLUDE_MODULES)? {
+            modules_block(&plugin_name, &plugin_var, uses_zplugins)
+        } else {
+            String::new()
+        },
+    );
+    ctx.insert(
+        V_ENV_SAVE_BLOCK,
+        &env_save_block(&plugin_name, &plugin_var, uses_zplugins, &env_vars),
+    );
+    ctx.insert(
+        V_ENV_RESTORE_BLOCK,
+        &env_restore_block(&plugin_name, &plugin_var, uses_zplugins, &env_vars),
+    );
+    ctx.insert(
+        V_HOOK_FUNCTIONS_BLOCK,
+        &hook_functions_block(&plugin_name, uses_zplugins, &hooks),
+    );
+    ctx.insert(
+        V_HOOKS_ADD_BLOCK,
+        &hooks_add_block(&plugin_name, &plugin_var, uses_zplugins, &hooks),
+    );
+    ctx.insert(
+        V_HOOKS_REMOVE_BLOCK,
+        &hooks_remove_block(&plugin_name, &hooks),
+    );
+    Ok(())
+}
+
+fn render_hooks(
+    tera: &mut Tera,
+    ctx: &Context,
+    hooks_dir: &Path,
+    pre_push: bool,
+    force: bool,
+) -> Result<(), Error> {
+    let pre_commit = hooks_dir.join(P_PRE_COMMIT);
+    render_template(tera, ctx, T_PRE_COMMIT_HOOK, &pre_commit, force)?;
+    make_executable(&pre_commit)?;
+    if pre_push {
+        let pre_push = hooks_dir.join(P_PRE_PUSH);
+        render_template(tera, ctx, T_PRE_PUSH_HOOK, &pre_push, force)?;
+        make_executable(&pre_push)?;
+    }
+    Ok(())
+}
+
+fn render_module(
+    tera: &mut Tera,
+    ctx: &Context,
+    modules_dir: &Path,
+    module_name: &str,
+    force: bool,
+) -> Result<(), Error> {
+    let mut ctx = ctx.clone();
+    ctx.insert(V_MODULE_NAME, module_name);
+    ctx.insert(V_MODULE_FN_NAME, &module_name.replace('-', "_"));
+    render_template(
+        tera,
+        &ctx,
+        T_MODULE,
+        &modules_dir.join(format!("{module_name}.zsh")),
+        force,
+    )
+}
+
+fn make_repository(path: &Path, ctx: &Context, force: bool) -> Result<(), Error> {
     trace!("make_repository => in path: {path:?}, force: {force}");
 
-    let repo_dir = path.join(".git");
+    let repo_dir = path.join(P_GIT_DIR);
     if !repo_dir.exists() || (repo_dir.is_dir() && force) {
-        if let Err(e) = Repository::init(path) {
-            error!("Error initializing new Git repository, error: {e}");
-            Err(e.into())
-        } else {
+        let branch = ctx_get_str(ctx, V_GIT_BRANCH)?;
+        let repository =
+            Repository::init_opts(path, RepositoryInitOptions::new().initial_head(branch))
+                .map_err(|e| {
+                    error!("Error initializing new Git repository, error: {e}");
+                    Error::from(e)
+                })?;
+        report_progress!();
+
+        if ctx_get_bool(ctx, O_INCLUDE_GIT_HOOKS)? {
+            set_hooks_path(&repository).map_err(|e| {
+                error!("Error setting 'core.hooksPath' in new Git repository, error: {e}");
+                Error::from(e)
+            })?;
+        }
+
+        if ctx_get_bool(ctx, O_INCLUDE_GIT_REMOTE)? && repository.find_remote("origin").is_err() {
+            let url = ctx_get_str(ctx, V_REPOSITORY_CLONE_URL)?;
+            repository.remote("origin", url).map_err(|e| {
+                error!("Error adding remote {url:?} to new Git repository, error: {e}");
+                Error::from(e)
+            })?;
             report_progress!();
-            Ok(())
         }
+        Ok(())
     } else {
         error!("Target Git repository path {repo_dir:?} already exists");
         Err(Error::TargetExists {
@@ -220,6 +541,65 @@ fn make_repository(path: &Path, force: bool) -> Result<(), Error> {
     }
 }
 
+/// Point Git at the committed hooks directory rather than '.git/hooks'.
+fn set_hooks_path(repository: &Repository) -> Result<(), git2::Error> {
+    repository
+        .config()?
+        .set_str("core.hooksPath", P_GIT_HOOKS_DIR)
+}
+
+/// Stage all generated files, respecting '.gitignore', and commit them.
+fn make_initial_commit(path: &Path, ctx: &Context) -> Result<(), Error> {
+    trace!("make_initial_commit => in path: {path:?}");
+
+    let commit = || -> Result<(), git2::Error> {
+        let repository = Repository::open(path)?;
+        let signature = repository.signature().or_else(|_| {
+            let user = ctx_get_str(ctx, V_AUTHOR)
+                .or_else(|_| ctx_get_str(ctx, V_REPOSITORY_OWNER))
+                .unwrap_or("zsh-plugin");
+            let email = ctx_get_str(ctx, V_AUTHOR_EMAIL)
+                .map(str::to_string)
+                .unwrap_or_else(|_| {
+                    let host = ctx_get_str(ctx, V_REPOSITORY_HOST).unwrap_or("github.com");
+                    format!("{user}@users.noreply.{host}")
+                });
+            Signature::now(user, &email)
+        })?;
+        let mut index = repository.index()?;
+        index.add_all(["*"], IndexAddOption::DEFAULT, None)?;
+        index.write()?;
+        let tree = repository.find_tree(index.write_tree()?)?;
+        repository.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Initial plugin structure generated by zsh-plugin",
+            &tree,
+            &[],
+        )?;
+        Ok(())
+    };
+    commit().map_err(|e| {
+        error!("Error creating initial commit in new Git repository, error: {e}");
+        Error::from(e)
+    })?;
+    report_progress!();
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<(), Error> {
+    use std::os::unix::fs::PermissionsExt;
+    set_permissions(path, PermissionsExt::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<(), Error> {
+    Ok(())
+}
+
 fn make_directory(path: &Path, force: bool) -> Result<(), Error> {
     trace!("make_directory => path: {path:?}', force: {force}");
 
@@ -275,8 +655,16 @@ impl From<InitCommand> for Context {
         ctx.insert(O_INCLUDE_BASH_WRAPPER, &cmd.add_bash_wrapper());
         ctx.insert(O_INCLUDE_BIN_DIR, &cmd.add_bin_dir());
         ctx.insert(O_INCLUDE_FUNCTIONS_DIR, &!cmd.no_functions_dir());
-        ctx.insert(O_INCLUDE_GITHUB_DIR, &!cmd.no_github_dir());
+        ctx.insert(
+            O_INCLUDE_CI,
+            &(cmd.ci() != CiTarget::Disabled && (!cmd.no_shell_check() || !cmd.no_shell_spec())),
+        );
         ctx.insert(O_INCLUDE_GIT_INIT, &!cmd.no_git_init());
+        ctx.insert(O_INCLUDE_GIT_COMMIT, &!cmd.no_initial_commit());
+        ctx.insert(O_INCLUDE_GIT_REMOTE, &!cmd.no_git_remote());
+        ctx.insert(O_INCLUDE_GIT_HOOKS, &cmd.add_hooks());
+        ctx.insert(O_INCLUDE_GIT_PRE_PUSH, &cmd.add_pre_push_hook());
+        ctx.insert(O_INCLUDE_MODULES, &!cmd.add_modules().is_empty());
         ctx.insert(O_INCLUDE_README, &!cmd.no_readme());
         ctx.insert(O_INCLUDE_SHELL_CHECK, &!cmd.no_shell_check());
         ctx.insert(O_INCLUDE_SHELL_DOC, &!cmd.no_shell_doc());
@@ -293,9 +681,177 @@ impl From<InitCommand> for Context {
         ctx.insert(V_PLUGIN_DISPLAY_NAME, &display_name);
         ctx.insert(V_PLUGIN_NAME, &plugin_name);
         ctx.insert(V_PLUGIN_VAR, &plugin_var);
-        ctx.insert(V_GITHUB_USER, cmd.github_user());
+        ctx.insert(V_GIT_BRANCH, cmd.initial_branch());
+        ctx.insert(V_PLUGIN_VERSION, &cmd.version().to_string());
+        ctx.insert(V_COPYRIGHT_YEAR, &Local::now().year());
+        if let Some(author) = cmd.author() {
+            ctx.insert(V_AUTHOR, author);
+        }
+        if let Some(email) = cmd.email() {
+            ctx.insert(V_AUTHOR_EMAIL, email);
+        }
+        if let Some(license) = cmd.license() {
+            ctx.insert(V_LICENSE, license);
+        }
+        ctx.insert(V_CI, cmd.ci().as_str());
+        let repository = cmd
+            .repository()
+            .cloned()
+            .or_else(|| RemoteRepository::from_git_remote(&target_root(&plugin_name)))
+            .unwrap_or_else(|| {
+                RemoteRepository::new(
+                    cmd.ci().default_host(),
+                    &var("USER").unwrap_or_else(|_| "USER".to_string()),
+                    &target_root(&plugin_name).to_string_lossy(),
+                )
+            })
+            .with_homepage(cmd.homepage());
+        ctx.insert(V_REPOSITORY_HOST, repository.host());
+        ctx.insert(V_REPOSITORY_OWNER, repository.owner());
+        ctx.insert(V_REPOSITORY_NAME, repository.name());
+        ctx.insert(V_REPOSITORY_URL, &repository.url());
+        ctx.insert(V_REPOSITORY_CLONE_URL, &repository.clone_url());
+        if let Some(homepage) = repository.homepage() {
+            ctx.insert(V_REPOSITORY_HOMEPAGE, homepage);
+        }
+        ctx.insert(
+            V_DEPENDENCIES,
+            &cmd.depends_on()
+                .iter()
+                .map(Name::to_string)
+                .collect::<Vec<_>>(),
+        );
+        ctx.insert(V_REQUIREMENTS, &cmd.requires_command());
+        ctx.insert(V_ENV_VARS, cmd.env());
+        ctx.insert(V_HOOKS, cmd.add_hook());
+        ctx.insert(
+            V_MODULES,
+            &cmd.add_modules()
+                .iter()
+                .map(Name::to_string)
+                .collect::<Vec<_>>(),
+        );
         ctx.insert("_shv_start", "${");
         ctx.insert("_shv_end", "}");
         ctx
     }
 }
+
+impl From<&Plugin> for Context {
+    fn from(plugin: &Plugin) -> Self {
+        let mut ctx = Context::new();
+        ctx.insert(O_USE_PLAIN_PLUGINS, &!plugin.uses_zplugins());
+        ctx.insert(V_PLUGIN_DISPLAY_NAME, plugin.display_name());
+        ctx.insert(V_PLUGIN_NAME, plugin.name());
+        ctx.insert(V_PLUGIN_VAR, &plugin.var());
+        ctx.insert("_shv_start", "${");
+        ctx.insert("_shv_end", "}");
+        ctx
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn init_context(args: &[&str]) -> Context {
+        let cmd = InitCommand::try_parse_from(["init", "demo", "--no-git-init"].iter().chain(args))
+            .unwrap();
+        let mut ctx = Context::from(cmd);
+        insert_blocks(&mut ctx).unwrap();
+        ctx
+    }
+
+    fn render(template: &str, ctx: &Context) -> String {
+        Tera::default().render_str(template, ctx).unwrap()
+    }
+
+    #[test]
+    fn render_zplugins_plugin_source() {
+        let source = render(
+            T_PLUGIN_SOURCE_ZPLUGINS,
+            &init_context(&["--add-hook", "precmd"]),
+        );
+        assert!(source.contains("    # zsh-plugin:begin:modules\n    # zsh-plugin:end:modules\n"));
+        assert!(source.contains("    add-zsh-hook precmd demo_precmd_hook\n"));
+        assert!(
+            source
+                .contains("    if [[ \"${HELPDIR}\" == \"${DEMO_PLUGIN_PATH:h}/help\" ]]; then\n")
+        );
+        assert!(source.contains(
+            "    autoload -Uz add-zsh-hook\n    # zsh-plugin:begin:hooks-remove\n    add-zsh-hook -d precmd demo_precmd_hook\n"
+        ));
+
+        let source = render(T_PLUGIN_SOURCE_ZPLUGINS, &init_context(&["-m", "core"]));
+        assert!(source.contains(&format!(
+            "    # zsh-plugin:begin:modules\n{}    # zsh-plugin:end:modules\n",
+            modules_block("demo", "DEMO", true)
+        )));
+    }
+
+    #[test]
+    fn render_plain_plugin_source() {
+        let source = render(T_PLUGIN_SOURCE, &init_context(&["-Z", "-a"]));
+        assert!(!source.contains("@zplugins_"));
+        assert!(source.contains("    # zsh-plugin:begin:modules\n    # zsh-plugin:end:modules\n"));
+        assert!(source.contains("    if [[ -n \"${DEMO[_PLUGIN_BIN_DIR]}\" ]]; then\n"));
+        assert!(source.contains("        HELPDIR=\"${DEMO[_PLUGIN_HELP_DIR]}\"\n"));
+        assert!(source.contains("            HELPDIR=\"${DEMO[_OLD_HELPDIR]}\"\n"));
+        assert!(
+            source.contains("    autoload -Uz add-zsh-hook\n    # zsh-plugin:begin:hooks-remove\n")
+        );
+
+        let source = render(T_PLUGIN_SOURCE, &init_context(&["-Z", "-m", "core"]));
+        assert!(source.contains(&format!(
+            "    # zsh-plugin:begin:modules\n{}    # zsh-plugin:end:modules\n",
+            modules_block("demo", "DEMO", false)
+        )));
+    }
+
+    #[test]
+    fn render_header_tags() {
+        let author = ["--author", "Jane Doe", "--email", "jane@example.com"];
+        let both = init_context(&[&author[..], &["--license", "MIT"]].concat());
+        for template in [T_PLUGIN_SOURCE, T_PLUGIN_SOURCE_ZPLUGINS] {
+            let source = render(template, &both);
+            assert!(source.contains("# @license MIT\n"));
+            assert!(source.contains(" Jane Doe <jane@example.com>\n#\n# @description"));
+            assert!(source.contains("# @copyright "));
+
+            let source = render(template, &init_context(&author));
+            assert!(!source.contains("# @license"));
+            assert!(source.contains("# @copyright "));
+
+            let source = render(template, &init_context(&["--license", "MIT"]));
+            assert!(source.contains("# @license MIT\n#\n# @description"));
+            assert!(!source.contains("# @copyright"));
+        }
+    }
+
+    #[test]
+    fn render_bash_wrapper() {
+        let wrapper = render(T_PLUGIN_WRAPPER, &init_context(&["-w", "-a"]));
+        assert!(wrapper.contains("    source \"$(.demo_install_path)/demo.plugin.zsh\"\n"));
+        assert!(wrapper.contains("declare -gA DEMO\n"));
+        assert!(wrapper.contains("        PATH=\"${PATH}:${DEMO[_PLUGIN_BIN_DIR]}\"\n"));
+        assert!(wrapper.contains("(BASH_VERSINFO[0] == 4 && BASH_VERSINFO[1] < 2)"));
+        assert!(wrapper.contains("    for file in \"${DEMO[_PLUGIN_MODS_DIR]}\"/*.zsh; do\n"));
+        assert!(wrapper.contains("demo_plugin_unload() {\n"));
+        assert!(wrapper.contains("        unset -f emulate\n"));
+    }
+
+    #[test]
+    fn render_module_source() {
+        let mut ctx = init_context(&["-m", "more-things"]);
+        ctx.insert(V_MODULE_NAME, "more-things");
+        ctx.insert(V_MODULE_FN_NAME, "more_things");
+        let module = render(T_MODULE, &ctx);
+        assert!(module.contains("# @name demo/more-things\n"));
+        assert!(module.contains("demo_more_things_example() {\n"));
+    }
+}
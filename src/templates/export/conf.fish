@@ -0,0 +1,12 @@
+# Fish port of the Zsh plugin {{ plugin_display_name }} {{ version }}, generated by zsh-plugin.
+{%- if description %}
+# {{ description }}
+{%- endif %}
+
+set -q {{ dir_var }}; or set -g {{ dir_var }} '{{ plugin_dir }}'
+{%- if has_bin %}
+fish_add_path --global --append "${{ dir_var }}/bin"
+{%- endif %}
+{%- for alias in aliases %}
+alias {{ alias.name }} '{{ alias.value }}'
+{%- endfor %}
@@ -0,0 +1,32 @@
+# Homebrew formula for the Zsh plugin {{ plugin_display_name }}, generated by zsh-plugin.
+class {{ formula_class }} < Formula
+{%- if description %}
+  desc "{{ description }}"
+{%- endif %}
+{%- if homepage %}
+  homepage "{{ homepage }}"
+{%- endif %}
+  url "{{ url }}"
+  sha256 "{{ sha256 }}"
+{%- if licenses | length == 1 %}
+  license "{{ licenses[0] }}"
+{%- elif licenses %}
+  license {{ license_operator }}: [{% for license in licenses %}"{{ license }}"{% if not loop.last %}, {% endif %}{% endfor %}]
+{%- endif %}
+
+  def install
+    (share/"{{ install_dir }}").install Dir["*"]
+  end
+
+  def caveats
+    <<~EOS
+      To activate {{ plugin_display_name }}, add the following to your ~/.zshrc:
+
+        source #{HOMEBREW_PREFIX}/share/{{ install_dir }}/{{ plugin_file }}
+    EOS
+  end
+
+  test do
+    assert_path_exists share/"{{ install_dir }}/{{ plugin_file }}"
+  end
+end
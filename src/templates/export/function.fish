@@ -0,0 +1,4 @@
+# Fish port of {{ function.name }} from the Zsh plugin {{ plugin_display_name }}, generated by zsh-plugin.
+function {{ function.name }}{% if function.description %} --description '{{ function.description }}'{% endif %}
+    {{ function.command }}
+end
@@ -0,0 +1,17 @@
+# Home Manager configuration for the Zsh plugin {{ plugin_display_name }}, generated by
+# zsh-plugin. This expects the derivation from 'zsh-plugin export nix' to be saved
+# as '{{ install_dir }}.nix' alongside this file.
+{ pkgs, ... }:
+
+let
+  plugin = pkgs.callPackage ./{{ install_dir }}.nix { };
+in
+{
+  programs.zsh.plugins = [
+    {
+      name = "{{ plugin_name }}";
+      src = "${plugin}/share/{{ install_dir }}";
+      file = "{{ plugin_file }}";
+    }
+  ];
+}
@@ -0,0 +1,35 @@
+# Nix derivation for the Zsh plugin {{ plugin_display_name }}, generated by zsh-plugin.
+{ lib, stdenvNoCC, fetchurl }:
+
+stdenvNoCC.mkDerivation {
+  pname = "{{ install_dir }}";
+  version = "{{ version }}";
+
+  src = fetchurl {
+    url = "{{ url }}";
+    sha256 = "{{ sha256 }}";
+  };
+
+  dontConfigure = true;
+  dontBuild = true;
+
+  installPhase = ''
+    runHook preInstall
+    mkdir -p $out/share/{{ install_dir }}
+    cp -R . $out/share/{{ install_dir }}
+    runHook postInstall
+  '';
+
+  meta = {
+{%- if description %}
+    description = "{{ description }}";
+{%- endif %}
+{%- if homepage %}
+    homepage = "{{ homepage }}";
+{%- endif %}
+{%- if licenses %}
+    license = map lib.getLicenseFromSpdxId [ {% for license in licenses %}"{{ license }}" {% endfor %}];
+{%- endif %}
+    platforms = lib.platforms.unix;
+  };
+}
@@ -0,0 +1,30 @@
+# shellcheck shell=sh
+#
+# POSIX sh port of the Zsh plugin {{ plugin_display_name }} {{ version }}, generated by zsh-plugin.
+{%- if description %}
+# {{ description }}
+{%- endif %}
+#
+
+if [ -z "${{ dir_var }}" ]; then
+    {{ dir_var }}='{{ plugin_dir }}'
+fi
+{%- if has_bin %}
+case ":$PATH:" in
+    *":${{ dir_var }}/bin:"*) ;;
+    *) PATH="$PATH:${{ dir_var }}/bin"; export PATH ;;
+esac
+{%- endif %}
+{%- for function in functions %}
+{% if function.description %}
+# {{ function.description }}
+{%- endif %}
+{{ function.name }}() {
+    {{ function.command }}
+}
+{%- endfor %}
+{%- if aliases %}
+{% for alias in aliases %}
+alias {{ alias.name }}='{{ alias.value }}'
+{%- endfor %}
+{%- endif %}
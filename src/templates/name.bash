@@ -1,10 +1,16 @@
 # -*- mode: sh; eval: (sh-set-shell "bash") -*-
+#
+# Load the {{ plugin_display_name }} plugin from Bash, or Zsh.
+#
+# Zsh sources the plugin file itself. Bash cannot parse the Zsh-only syntax the
+# plugin file uses, so this file sets up the plugin's state, its modules and its
+# public functions from the functions directory, without it. Functions and aliases
+# defined in the plugin file itself are not available from Bash. Run
+# `zsh-plugin check --bash` to find these, and any Zsh-only syntax in the files
+# sourced here. Requires Bash 4.2 or later, for `declare -gA`.
+#
 
-emulate() {
-    : # no-op
-}
-
-.install_path() {
+.{{ plugin_name }}_install_path() {
     local install_dir
     # shellcheck disable=SC2154
     if [[ -n "${ZSH_VERSION}" ]]; then
@@ -13,7 +19,7 @@ emulate() {
         install_dir="${BASH_SOURCE[1]}"
     else
         echo "Error: ¬Zsh ∧ ¬Bash ⟹ not supported."
-        exit 1
+        return 1
     fi
 
     if [[ "${install_dir}" == */* ]]; then
@@ -24,22 +30,108 @@ emulate() {
 
     if [[ -n "${ZSH_VERSION}" ]]; then
         install_dir="${install_dir:A}"
-    elif [[ -n "${BASH_VERSION}" ]]; then
-        install_dir=$(realpath "${install_dir}")
     else
-        echo "Error: ¬Zsh ∧ ¬Bash ⟹ not supported."
-        exit 1
+        install_dir=$(realpath "${install_dir}")
     fi
     printf '%s' "${install_dir}"
 }
 
-source "$(.install_path)/{{ plugin_name }}.plugin.zsh"
+if [[ -n "${ZSH_VERSION}" ]]; then
+    source "$(.{{ plugin_name }}_install_path)/{{ plugin_name }}.plugin.zsh"
+    unfunction .{{ plugin_name }}_install_path
+    return 0
+fi
 
-{% if include_functions_dir -%}
-if [[ -d "$(.install_path)/functions" ]]; then
-    for file in $(.install_path)/functions/*; do
-        source "${file}"
+if (( BASH_VERSINFO[0] < 4 || (BASH_VERSINFO[0] == 4 && BASH_VERSINFO[1] < 2) )); then
+    echo "Error: the {{ plugin_display_name }} plugin requires Bash 4.2 or later." >&2
+    unset -f .{{ plugin_name }}_install_path
+    return 1
+fi
+
+############################################################################
+# Bash implementation of the plugin's setup.
+#
+
+# shellcheck disable=SC2034
+declare -gA {{ plugin_var }}
+{{ plugin_var }}[_PLUGIN_DIR]="$(.{{ plugin_name }}_install_path)"
+{{ plugin_var }}[_FUNCTIONS]=""
+unset -f .{{ plugin_name }}_install_path
+
+# Functions start with `builtin emulate -L zsh`, which is rewritten to call this
+# no-op, unless another plugin already defined it.
+if ! declare -F emulate &> /dev/null; then
+    emulate() {
+        : # no-op
+    }
+    {{ plugin_var }}[_EMULATE_SHIM]=1
+fi
+
+{% if include_bin_dir -%}
+if [[ -d "{{ _shv_start }}{{ plugin_var }}[_PLUGIN_DIR]{{ _shv_end }}/bin" ]]; then
+    {{ plugin_var }}[_PLUGIN_BIN_DIR]="{{ _shv_start }}{{ plugin_var }}[_PLUGIN_DIR]{{ _shv_end }}/bin"
+    if [[ ":${PATH}:" != *":{{ _shv_start }}{{ plugin_var }}[_PLUGIN_BIN_DIR]{{ _shv_end }}:"* ]]; then
+        PATH="${PATH}:{{ _shv_start }}{{ plugin_var }}[_PLUGIN_BIN_DIR]{{ _shv_end }}"
+    fi
+fi
+
+{% endif -%}
+# Define a function for each file in the functions directory, as `autoload` would.
+if [[ -d "{{ _shv_start }}{{ plugin_var }}[_PLUGIN_DIR]{{ _shv_end }}/functions" ]]; then
+    {{ plugin_var }}[_PLUGIN_FNS_DIR]="{{ _shv_start }}{{ plugin_var }}[_PLUGIN_DIR]{{ _shv_end }}/functions"
+
+    for file in "{{ _shv_start }}{{ plugin_var }}[_PLUGIN_FNS_DIR]{{ _shv_end }}"/*; do
+        [[ -f "${file}" ]] || continue
+        fn="${file##*/}"
+        body="$(< "${file}")"
+        eval "${fn}() {
+${body//builtin emulate/emulate}
+}"
+        {{ plugin_var }}[_FUNCTIONS]="{{ _shv_start }}{{ plugin_var }}[_FUNCTIONS]{{ _shv_end }}{{ _shv_start }}{{ plugin_var }}[_FUNCTIONS]:+,{{ _shv_end }}${fn}"
     done
+    unset file fn body
 fi
+
+# Source each module, in lexical order, remembering the functions it defines.
+if [[ -d "{{ _shv_start }}{{ plugin_var }}[_PLUGIN_DIR]{{ _shv_end }}/modules" ]]; then
+    {{ plugin_var }}[_PLUGIN_MODS_DIR]="{{ _shv_start }}{{ plugin_var }}[_PLUGIN_DIR]{{ _shv_end }}/modules"
+
+    for file in "{{ _shv_start }}{{ plugin_var }}[_PLUGIN_MODS_DIR]{{ _shv_end }}"/*.zsh; do
+        [[ -f "${file}" ]] || continue
+        # shellcheck disable=SC2046
+        fns_before="$(printf ' %s' $(compgen -A function)) "
+        body="$(< "${file}")"
+        eval "${body//builtin emulate/emulate}"
+        for fn in $(compgen -A function); do
+            if [[ "${fns_before}" != *" ${fn} "* ]]; then
+                {{ plugin_var }}[_FUNCTIONS]="{{ _shv_start }}{{ plugin_var }}[_FUNCTIONS]{{ _shv_end }}{{ _shv_start }}{{ plugin_var }}[_FUNCTIONS]:+,{{ _shv_end }}${fn}"
+            fi
+        done
+    done
+    unset file fn body fns_before
+fi
+
+{{ plugin_name }}_plugin_unload() {
+    local fn
+    local -a plugin_fns
+    IFS=',' read -r -a plugin_fns <<< "{{ _shv_start }}{{ plugin_var }}[_FUNCTIONS]{{ _shv_end }}"
+    for fn in "${plugin_fns[@]}"; do
+        unset -f "${fn}"
+    done
+{%- if include_bin_dir %}
+
+    if [[ -n "{{ _shv_start }}{{ plugin_var }}[_PLUGIN_BIN_DIR]{{ _shv_end }}" ]]; then
+        PATH=":${PATH}:"
+        PATH="${PATH//:{{ _shv_start }}{{ plugin_var }}[_PLUGIN_BIN_DIR]{{ _shv_end }}:/:}"
+        PATH="${PATH#:}"
+        PATH="${PATH%:}"
+    fi
 {%- endif %}
-unfunction .install_path
+
+    if [[ -n "{{ _shv_start }}{{ plugin_var }}[_EMULATE_SHIM]{{ _shv_end }}" ]]; then
+        unset -f emulate
+    fi
+
+    unset {{ plugin_var }}
+    unset -f {{ plugin_name }}_plugin_unload
+}
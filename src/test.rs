@@ -0,0 +1,76 @@
+//! Running the tests of an existing plugin.
+//!
+//! A plugin's ShellSpec tests are run by the `test` target of its generated
+//! `Makefile`, which runs them under both Zsh and Bash, so the `test` command runs
+//! `make test` in the plugin's directory, showing the output of `make` as it runs.
+//! A plugin generated without ShellSpec support has no `test` target, and cannot be
+//! tested this way.
+
+use crate::{error::Error, plugin::Plugin, templates::P_MAKEFILE};
+use std::{
+    fs::read_to_string,
+    process::{Command, ExitCode},
+};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+const MAKE_TARGET: &str = "test";
+
+/// Run the tests of `plugin` with the `test` target of its `Makefile`, and fail if
+/// they fail.
+pub(crate) fn test_plugin(plugin: &Plugin) -> Result<ExitCode, Error> {
+    trace!("test_plugin => plugin: {plugin:?}");
+    let makefile = plugin.root().join(P_MAKEFILE);
+    if !has_target(&read_to_string(&makefile).unwrap_or_default(), MAKE_TARGET) {
+        error!("No '{MAKE_TARGET}' target in {makefile:?}");
+        return Err(Error::TestsNotFound { path: makefile });
+    }
+
+    println!("Testing plugin '{}'", plugin.display_name());
+    let status = Command::new("make")
+        .arg(MAKE_TARGET)
+        .current_dir(plugin.root())
+        .status()?;
+
+    Ok(if status.success() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Returns `true` if the `Makefile` content defines a rule for `target`.
+fn has_target(makefile: &str, target: &str) -> bool {
+    makefile.lines().any(|line| {
+        line.split_once(':').is_some_and(|(targets, _)| {
+            !line.starts_with(char::is_whitespace)
+                && targets.split_whitespace().any(|name| name == target)
+        })
+    })
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_make_target() {
+        let makefile = "check: check_sources\n\ntest: $(SOURCES)\n\tshellspec\n";
+        assert!(has_target(makefile, "test"));
+        assert!(has_target(makefile, "check"));
+        assert!(!has_target(makefile, "check_sources"));
+        assert!(!has_target("\tmake test: now\n", "test"));
+        assert!(!has_target("testing: $(SOURCES)\n", "test"));
+    }
+}
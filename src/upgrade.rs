@@ -0,0 +1,107 @@
+//! Upgrading an existing plugin to the current version of this tool.
+//!
+//! The `upgrade` command brings up to date the parts of a plugin this tool
+//! generated, so that a plugin generated by an earlier version can be maintained
+//! by the current one.
+//!
+//! 1. **README**; the generated sections of `README.md` are rewritten.
+//! 2. **Manifest**; the `plugin.toml` manifest is created, or brought up to date,
+//!    and this version of the tool recorded as its generator.
+//! 3. **Plugin file**; the plugin file is searched for the generated blocks this
+//!    version of the tool maintains. Blocks are not added to existing code, so
+//!    any that are missing are reported, and must be added by hand.
+//!
+//! The command fails if any generated block is missing from the plugin file.
+
+use crate::{
+    blocks::{PLUGIN_FILE_BLOCKS, block_content},
+    error::Error,
+    manifest::Manifest,
+    plugin::Plugin,
+    readme::{readme_is_current, update_readme},
+    templates::P_README,
+};
+use std::{fs::read_to_string, process::ExitCode};
+use tracing::trace;
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Upgrade the README, manifest and plugin file of `plugin`, reporting the outcome
+/// of each, and fail if the plugin file is missing any generated block.
+pub(crate) fn upgrade_plugin(plugin: &Plugin) -> Result<ExitCode, Error> {
+    trace!("upgrade_plugin => plugin: {plugin:?}");
+    let readme = upgrade_readme(plugin)?;
+    let manifest = upgrade_manifest(plugin)?;
+    let missing = missing_blocks(plugin)?;
+
+    println!("Upgrading plugin '{}'", plugin.display_name());
+    println!("├─ README: {readme}");
+    println!("├─ Manifest: {manifest}");
+    if missing.is_empty() {
+        println!("└─ Plugin file: up to date");
+        Ok(ExitCode::SUCCESS)
+    } else {
+        println!(
+            "└─ Plugin file: missing blocks {}",
+            missing
+                .iter()
+                .map(|name| format!("'{name}'"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        eprintln!(
+            r#"
+The plugin file is missing generated blocks.
+├─ Path: {:?}
+└─ Help: Copy the missing blocks, with their marker comments, from a plugin generated by 'zsh-plugin init'."#,
+            plugin.plugin_file()
+        );
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn upgrade_readme(plugin: &Plugin) -> Result<String, Error> {
+    if !plugin.root().join(P_README).is_file() {
+        return Ok(format!("skipped, no '{P_README}' file"));
+    }
+    Ok(match readme_is_current(plugin) {
+        Ok(true) => "up to date".to_string(),
+        Ok(false) => {
+            update_readme(plugin)?;
+            "updated".to_string()
+        }
+        Err(Error::MarkerNotFound { marker, .. }) => format!("skipped, no '{marker}' marker"),
+        Err(e) => return Err(e),
+    })
+}
+
+fn upgrade_manifest(plugin: &Plugin) -> Result<&'static str, Error> {
+    let current = Manifest::load(plugin)?;
+    let upgraded = Manifest::upgraded(plugin)?;
+    Ok(match current {
+        Some(current) if current == upgraded => "up to date",
+        Some(_) => {
+            upgraded.save(plugin)?;
+            "updated"
+        }
+        None => {
+            upgraded.save(plugin)?;
+            "created"
+        }
+    })
+}
+
+/// The generated blocks that are missing from the plugin file.
+fn missing_blocks(plugin: &Plugin) -> Result<Vec<&'static str>, Error> {
+    let content = read_to_string(plugin.plugin_file())?;
+    Ok(PLUGIN_FILE_BLOCKS
+        .into_iter()
+        .filter(|name| block_content(&content, name).is_none())
+        .collect())
+}
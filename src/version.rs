@@ -0,0 +1,559 @@
+//! Semantic version management for existing plugins.
+//!
+//! A plugin's version is recorded in the `@version` tag of the main plugin file's
+//! header. Bumping the version updates this tag, adds a new dated section to the
+//! plugin's `CHANGELOG.md`, or if that does not exist the "Changes" section of
+//! its `README.md`, and may optionally commit and tag the release.
+//!
+//! Change entries may be derived from the [conventional commits](https://www.conventionalcommits.org/)
+//! made since the tag of the previous version, which is named `vMAJOR.MINOR.PATCH`.
+
+use crate::{
+    cli::VersionPart,
+    error::Error,
+    manifest::{Manifest, P_MANIFEST, update_manifest},
+    plugin::Plugin,
+    templates::P_README,
+};
+use chrono::Local;
+use flat_error::FlatError;
+use git2::{ObjectType, Oid, Repository, Signature, Sort};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter, Result as FmtResult, Write},
+    fs::{read_to_string, write},
+    path::Path,
+    process::ExitCode,
+    str::FromStr,
+};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// A semantic version, `MAJOR.MINOR.PATCH`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Print the current version of `plugin`.
+pub(crate) fn show_version(plugin: &Plugin) -> Result<ExitCode, Error> {
+    trace!("show_version => plugin: {plugin:?}");
+    println!("{}", current_version(plugin)?);
+    Ok(ExitCode::SUCCESS)
+}
+
+/// The current version of `plugin`, from the `@version` tag of its plugin file.
+pub(crate) fn current_version(plugin: &Plugin) -> Result<Version, Error> {
+    read_version(&read_to_string(plugin.plugin_file())?)
+}
+
+/// Increment the version of `plugin` and record a new section of changes.
+pub(crate) fn bump_version(
+    plugin: &Plugin,
+    part: VersionPart,
+    from_commits: bool,
+    tag: bool,
+) -> Result<ExitCode, Error> {
+    trace!(
+        "bump_version => plugin: {plugin:?}, part: {part:?}, from_commits: {from_commits}, tag: {tag}"
+    );
+    let plugin_file = plugin.plugin_file();
+    let source = read_to_string(&plugin_file)?;
+    let current = read_version(&source)?;
+    let next = current.bump(part);
+
+    let repository = if from_commits || tag {
+        Some(Repository::open(plugin.root()).map_err(git_error)?)
+    } else {
+        None
+    };
+
+    // Check everything that could stop the release before changing any file.
+    let signature = match &repository {
+        Some(repository) if tag => Some(release_signature(repository, plugin, &next)?),
+        _ => None,
+    };
+
+    let entries = match &repository {
+        Some(repository) if from_commits => conventional_entries(repository, &current)?,
+        _ => Vec::default(),
+    };
+    let section = changes_section(
+        &next,
+        &Local::now().format("%Y-%m-%d").to_string(),
+        &entries,
+    );
+
+    let plugin_file_name = Path::new(plugin_file.file_name().unwrap_or_default());
+    let mut changed_files = vec![plugin_file_name];
+    write(&plugin_file, set_version(&source, &next))?;
+    let manifest = plugin.root().join(P_MANIFEST);
+    let manifest_before = read_to_string(&manifest).ok();
+    update_manifest(plugin)?;
+    if manifest_before.is_some() && read_to_string(&manifest).ok() != manifest_before {
+        changed_files.push(Path::new(P_MANIFEST));
+    }
+    print!(".");
+
+    let changelog = plugin.root().join(P_CHANGELOG);
+    let readme = plugin.root().join(P_README);
+    if changelog.is_file() {
+        let content = read_to_string(&changelog)?;
+        write(&changelog, insert_changelog_section(&content, &section))?;
+        changed_files.push(Path::new(P_CHANGELOG));
+    } else if readme.is_file() {
+        let content = read_to_string(&readme)?;
+        write(&readme, insert_readme_section(&content, &section))?;
+        changed_files.push(Path::new(P_README));
+    }
+    print!(".");
+
+    if let Some(repository) = repository
+        && let Some(signature) = signature
+    {
+        commit_and_tag(&repository, &signature, &changed_files, &next)?;
+        print!(".");
+    }
+
+    println!(" Done");
+    println!("{current} → {next}");
+
+    Ok(ExitCode::SUCCESS)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for Version {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidVersion {
+            value: s.to_string(),
+        };
+        let parts = s
+            .trim()
+            .trim_start_matches('v')
+            .split('.')
+            .map(|p| p.parse::<u64>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+        match parts.as_slice() {
+            [major, minor, patch] => Ok(Self {
+                major: *major,
+                minor: *minor,
+                patch: *patch,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Version {
+    /// The version of a newly generated plugin, `0.1.0`.
+    pub(crate) fn initial() -> Self {
+        Self {
+            minor: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the next version, incrementing `part` and resetting lesser parts.
+    pub(crate) fn bump(&self, part: VersionPart) -> Self {
+        match part {
+            VersionPart::Major => Self {
+                major: self.major + 1,
+                minor: 0,
+                patch: 0,
+            },
+            VersionPart::Minor => Self {
+                minor: self.minor + 1,
+                patch: 0,
+                ..*self
+            },
+            VersionPart::Patch => Self {
+                patch: self.patch + 1,
+                ..*self
+            },
+        }
+    }
+
+    /// The name of the Git tag for this version.
+    pub(crate) fn tag_name(&self) -> String {
+        format!("v{self}")
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+const P_CHANGELOG: &str = "CHANGELOG.md";
+
+const T_VERSION: &str = "# @version";
+
+const CHANGE_GROUPS: &[(&str, &str)] = &[("feat", "Features"), ("fix", "Bug Fixes")];
+const OTHER_CHANGES: &str = "Other Changes";
+
+fn git_error(source: git2::Error) -> Error {
+    error!("Git operation failed, error: {source}");
+    Error::Git {
+        source: FlatError::from_any(&source),
+    }
+}
+
+fn read_version(source: &str) -> Result<Version, Error> {
+    source
+        .lines()
+        .find_map(|line| line.strip_prefix(T_VERSION))
+        .ok_or_else(|| Error::InvalidVersion {
+            value: String::new(),
+        })?
+        .parse()
+}
+
+fn set_version(source: &str, version: &Version) -> String {
+    let mut result = source
+        .lines()
+        .map(|line| {
+            if line.starts_with(T_VERSION) {
+                format!("{T_VERSION} {version}")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if source.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// Parse a conventional commit summary, returning the type and description.
+fn conventional_commit(summary: &str) -> Option<(&str, &str)> {
+    let (prefix, description) = summary.split_once(": ")?;
+    let prefix = prefix.trim_end_matches('!');
+    let kind = match prefix.split_once('(') {
+        Some((kind, scope)) if scope.ends_with(')') => kind,
+        Some(_) => return None,
+        None => prefix,
+    };
+    (!kind.is_empty() && kind.chars().all(|c| c.is_ascii_lowercase()))
+        .then_some((kind, description.trim()))
+}
+
+/// Collect the conventional commit summaries since the tag for `since`, as
+/// `(type, description)` pairs, newest first.
+fn conventional_entries(
+    repository: &Repository,
+    since: &Version,
+) -> Result<Vec<(String, String)>, Error> {
+    if repository.head().is_err() {
+        return Ok(Vec::default());
+    }
+    let stop: Option<Oid> = repository
+        .revparse_single(&format!("refs/tags/{}", since.tag_name()))
+        .and_then(|tag| tag.peel(ObjectType::Commit))
+        .map(|commit| commit.id())
+        .ok();
+
+    let mut walk = repository.revwalk().map_err(git_error)?;
+    walk.set_sorting(Sort::TOPOLOGICAL).map_err(git_error)?;
+    walk.push_head().map_err(git_error)?;
+
+    let mut entries = Vec::default();
+    for oid in walk {
+        let oid = oid.map_err(git_error)?;
+        if Some(oid) == stop {
+            break;
+        }
+        let commit = repository.find_commit(oid).map_err(git_error)?;
+        if let Some((kind, description)) = commit.summary().and_then(conventional_commit) {
+            entries.push((kind.to_string(), description.to_string()));
+        }
+    }
+    Ok(entries)
+}
+
+/// Render the body of a changes section, without the heading.
+fn changes_section(version: &Version, date: &str, entries: &[(String, String)]) -> String {
+    let mut groups: BTreeMap<usize, Vec<&str>> = BTreeMap::default();
+    for (kind, description) in entries {
+        let group = CHANGE_GROUPS
+            .iter()
+            .position(|(k, _)| k == kind)
+            .unwrap_or(CHANGE_GROUPS.len());
+        groups.entry(group).or_default().push(description);
+    }
+
+    let mut section = format!("Version {version} ({date})\n\n");
+    if groups.is_empty() {
+        section.push_str("TBD\n");
+    }
+    for (group, descriptions) in groups {
+        let title = CHANGE_GROUPS
+            .get(group)
+            .map(|(_, title)| *title)
+            .unwrap_or(OTHER_CHANGES);
+        let _ = writeln!(section, "{title}:\n");
+        for description in descriptions {
+            let _ = writeln!(section, "* {description}");
+        }
+        section.push('\n');
+    }
+    section.truncate(section.trim_end().len());
+    section.push('\n');
+    section
+}
+
+/// Insert `section` as a level 2 heading before the first existing version.
+fn insert_changelog_section(content: &str, section: &str) -> String {
+    let heading = format!("## {section}");
+    match content.find("\n## ") {
+        Some(index) => format!("{}{heading}\n{}", &content[..=index], &content[index + 1..]),
+        None => format!("{}\n\n{heading}", content.trim_end()),
+    }
+}
+
+/// Insert `section` as a level 3 heading at the start of the "Changes" section.
+fn insert_readme_section(content: &str, section: &str) -> String {
+    const CHANGES: &str = "## Changes\n";
+    let heading = format!("### {section}");
+    match content.find(CHANGES) {
+        Some(index) => {
+            let start = index + CHANGES.len();
+            let rest = content[start..].trim_start_matches('\n');
+            if rest.is_empty() {
+                format!("{}\n{heading}", &content[..start])
+            } else {
+                format!("{}\n{heading}\n{rest}", &content[..start])
+            }
+        }
+        None => format!("{}\n\n{CHANGES}\n{heading}", content.trim_end()),
+    }
+}
+
+/// The signature to commit and tag the release of `version` with, once it is
+/// known that the tag is free and that the index has no other staged changes,
+/// which the release commit would otherwise include.
+fn release_signature(
+    repository: &Repository,
+    plugin: &Plugin,
+    version: &Version,
+) -> Result<Signature<'static>, Error> {
+    let tag_name = version.tag_name();
+    if repository
+        .find_reference(&format!("refs/tags/{tag_name}"))
+        .is_ok()
+    {
+        return Err(git_error(git2::Error::from_str(&format!(
+            "the tag '{tag_name}' already exists"
+        ))));
+    }
+
+    let head_tree = repository
+        .head()
+        .ok()
+        .and_then(|head| head.peel_to_tree().ok());
+    let staged = repository
+        .diff_tree_to_index(head_tree.as_ref(), None, None)
+        .map_err(git_error)?;
+    if staged.deltas().len() > 0 {
+        return Err(git_error(git2::Error::from_str(
+            "the index has staged changes that are not part of the release",
+        )));
+    }
+
+    repository
+        .signature()
+        .or_else(|_| {
+            let author = Manifest::current(plugin)
+                .ok()
+                .and_then(|manifest| manifest.plugin.authors.into_iter().next());
+            let (name, email) = author
+                .as_deref()
+                .and_then(author_parts)
+                .map(|(name, email)| (name.to_string(), email.to_string()))
+                .unwrap_or_else(|| {
+                    (
+                        "zsh-plugin".to_string(),
+                        "zsh-plugin@users.noreply.github.com".to_string(),
+                    )
+                });
+            Signature::now(&name, &email)
+        })
+        .map_err(git_error)
+}
+
+/// The name and email of an author of the form `NAME <EMAIL>`.
+fn author_parts(author: &str) -> Option<(&str, &str)> {
+    let (name, email) = author.trim().strip_suffix('>')?.split_once('<')?;
+    let (name, email) = (name.trim(), email.trim());
+    (!name.is_empty() && !email.is_empty()).then_some((name, email))
+}
+
+fn commit_and_tag(
+    repository: &Repository,
+    signature: &Signature<'_>,
+    files: &[&Path],
+    version: &Version,
+) -> Result<(), Error> {
+    let mut index = repository.index().map_err(git_error)?;
+    for file in files {
+        index.add_path(file).map_err(git_error)?;
+    }
+    index.write().map_err(git_error)?;
+    let tree = repository
+        .find_tree(index.write_tree().map_err(git_error)?)
+        .map_err(git_error)?;
+
+    let parent = repository
+        .head()
+        .ok()
+        .and_then(|head| head.peel_to_commit().ok());
+    let parents = parent.iter().collect::<Vec<_>>();
+    let message = format!("chore(release): version {version}");
+    let commit = repository
+        .commit(
+            Some("HEAD"),
+            signature,
+            signature,
+            &message,
+            &tree,
+            &parents,
+        )
+        .map_err(git_error)?;
+
+    let target = repository
+        .find_object(commit, Some(ObjectType::Commit))
+        .map_err(git_error)?;
+    repository
+        .tag(
+            &version.tag_name(),
+            &target,
+            signature,
+            &format!("Version {version}"),
+            false,
+        )
+        .map_err(git_error)?;
+    Ok(())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_display() {
+        let version: Version = "1.2.3".parse().unwrap();
+        assert_eq!(version.to_string(), "1.2.3");
+        assert_eq!("v0.1.0".parse::<Version>().unwrap().to_string(), "0.1.0");
+        assert!("1.2".parse::<Version>().is_err());
+        assert!("**use semantic versioning**".parse::<Version>().is_err());
+    }
+
+    #[test]
+    fn bump_parts() {
+        let version: Version = "1.2.3".parse().unwrap();
+        assert_eq!(version.bump(VersionPart::Major).to_string(), "2.0.0");
+        assert_eq!(version.bump(VersionPart::Minor).to_string(), "1.3.0");
+        assert_eq!(version.bump(VersionPart::Patch).to_string(), "1.2.4");
+    }
+
+    #[test]
+    fn read_and_set_version() {
+        let source = "# @name x\n# @version 0.1.0\n#\n";
+        let version = read_version(source).unwrap();
+        assert_eq!(version.to_string(), "0.1.0");
+        assert_eq!(
+            set_version(source, &version.bump(VersionPart::Minor)),
+            "# @name x\n# @version 0.2.0\n#\n"
+        );
+        assert!(read_version("# @name x\n").is_err());
+    }
+
+    #[test]
+    fn parse_author_parts() {
+        assert_eq!(
+            author_parts("Jane Doe <jane@example.com>"),
+            Some(("Jane Doe", "jane@example.com"))
+        );
+        assert_eq!(author_parts("Jane Doe"), None);
+        assert_eq!(author_parts("<jane@example.com>"), None);
+    }
+
+    #[test]
+    fn parse_conventional_commits() {
+        assert_eq!(
+            conventional_commit("feat: add thing"),
+            Some(("feat", "add thing"))
+        );
+        assert_eq!(
+            conventional_commit("fix(parser)!: handle it"),
+            Some(("fix", "handle it"))
+        );
+        assert_eq!(conventional_commit("Update README"), None);
+        assert_eq!(conventional_commit("Merge: a b"), None);
+        assert_eq!(conventional_commit("not a type: thing"), None);
+    }
+
+    #[test]
+    fn render_changes_section() {
+        let version: Version = "0.2.0".parse().unwrap();
+        assert_eq!(
+            changes_section(&version, "2025-01-01", &[]),
+            "Version 0.2.0 (2025-01-01)\n\nTBD\n"
+        );
+        let entries = vec![
+            ("docs".to_string(), "more docs".to_string()),
+            ("fix".to_string(), "a bug".to_string()),
+            ("feat".to_string(), "a feature".to_string()),
+        ];
+        assert_eq!(
+            changes_section(&version, "2025-01-01", &entries),
+            "Version 0.2.0 (2025-01-01)\n\nFeatures:\n\n* a feature\n\nBug Fixes:\n\n* a bug\n\nOther Changes:\n\n* more docs\n"
+        );
+    }
+
+    #[test]
+    fn insert_readme_changes() {
+        let readme = "# Title\n\n## Changes\n\n### Version 0.1.0\n\nTBD\n";
+        assert_eq!(
+            insert_readme_section(readme, "Version 0.2.0 (2025-01-01)\n\nTBD\n"),
+            "# Title\n\n## Changes\n\n### Version 0.2.0 (2025-01-01)\n\nTBD\n\n### Version 0.1.0\n\nTBD\n"
+        );
+    }
+
+    #[test]
+    fn insert_changelog_changes() {
+        let changelog = "# Changelog\n\n## Version 0.1.0\n\nTBD\n";
+        assert_eq!(
+            insert_changelog_section(changelog, "Version 0.2.0 (2025-01-01)\n\nTBD\n"),
+            "# Changelog\n\n## Version 0.2.0 (2025-01-01)\n\nTBD\n\n## Version 0.1.0\n\nTBD\n"
+        );
+        assert_eq!(
+            insert_changelog_section("# Changelog\n", "Version 0.1.0 (2025-01-01)\n\nTBD\n"),
+            "# Changelog\n\n## Version 0.1.0 (2025-01-01)\n\nTBD\n"
+        );
+    }
+}
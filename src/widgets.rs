@@ -0,0 +1,219 @@
+//! Line editor (ZLE) widgets added by a plugin, and removed when it is unloaded.
+//!
+//! For each widget a function, `NAME_WIDGET_widget`, is added to the plugin file
+//! between the `widget-functions` markers for the author to complete. The
+//! `widgets-add` block of the plugin's `_plugin_init` function defines the widget
+//! with `zle -N` and, if it has a key binding, saves the widget currently bound to
+//! that key sequence before binding it with `bindkey`. The `widgets-remove` block
+//! of `_plugin_unload` restores the previous binding and deletes the widget with
+//! `zle -D`, if init defined it. Plain plugins track their widgets, and the saved bindings, in the
+//! `_WIDGETS` and `_OLD_BINDING_WIDGET` keys of the plugin hash.
+
+use crate::{
+    blocks::{
+        B_WIDGET_FUNCTIONS, B_WIDGETS_ADD, B_WIDGETS_REMOVE, append_to_block, block_content,
+        widget_functions_block, widgets_add_block, widgets_remove_block,
+    },
+    error::Error,
+    manifest::update_manifest,
+    plugin::Plugin,
+    readme::update_readme,
+};
+use clap::ValueEnum;
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs::read_to_string,
+    process::ExitCode,
+    str::FromStr,
+};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// A line editor widget, with an optional key binding, added by a plugin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Widget {
+    name: String,
+    binding: Option<KeyBinding>,
+}
+
+/// A key sequence bound to a widget in a keymap.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct KeyBinding {
+    key_sequence: String,
+    keymap: Keymap,
+}
+
+/// The keymap a widget's key binding is added to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, ValueEnum)]
+pub(crate) enum Keymap {
+    /// The keymap currently linked to the main keymap, usually emacs or viins.
+    #[default]
+    Main,
+
+    /// The vi insert mode keymap.
+    Viins,
+
+    /// The vi command mode keymap.
+    Vicmd,
+
+    /// The emacs keymap.
+    Emacs,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Add `widget` to the plugin, defined and bound by its init function and removed
+/// by its unload function.
+pub(crate) fn add_widget(plugin: &Plugin, widget: &Widget) -> Result<ExitCode, Error> {
+    trace!("add_widget => plugin: {plugin:?}, widget: {widget:?}");
+    let plugin_file = plugin.plugin_file();
+    let source = read_to_string(&plugin_file)?;
+    let existing = block_content(&source, B_WIDGET_FUNCTIONS).unwrap_or_default();
+    let name = plugin.name();
+
+    if existing.contains(&format!("{}() {{", widget.function_name(name))) {
+        eprintln!(
+            "The plugin already has the widget '{}', ignoring.",
+            widget.name()
+        );
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let (var, zplugins) = (plugin.var(), plugin.uses_zplugins());
+    append_to_block(
+        &plugin_file,
+        B_WIDGET_FUNCTIONS,
+        &widget_functions_block(name, zplugins, widget),
+    )?;
+    print!(".");
+    append_to_block(
+        &plugin_file,
+        B_WIDGETS_ADD,
+        &widgets_add_block(name, &var, zplugins, widget),
+    )?;
+    print!(".");
+    append_to_block(
+        &plugin_file,
+        B_WIDGETS_REMOVE,
+        &widgets_remove_block(&var, zplugins, widget),
+    )?;
+    update_manifest(plugin)?;
+    update_readme(plugin)?;
+    println!(". Done");
+
+    println!("Added widget function: {}", widget.function_name(name));
+
+    Ok(ExitCode::SUCCESS)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl FromStr for Widget {
+    type Err = Error;
+
+    /// Parse a widget name, which may contain letters, digits, `-`, `_` and `.`,
+    /// but may not start with `.`, as those names are reserved for Zsh's own widgets.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty()
+            || s.starts_with('.')
+            || !s
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+        {
+            error!("Widget::from_str; value {s:?} is not a widget name");
+            return Err(Error::InvalidWidgetName {
+                value: s.to_string(),
+            });
+        }
+        Ok(Self {
+            name: s.to_string(),
+            binding: None,
+        })
+    }
+}
+
+impl Widget {
+    /// Bind the widget to `key_sequence`, in `bindkey` notation, in `keymap`.
+    pub(crate) fn with_binding(mut self, key_sequence: &str, keymap: Keymap) -> Self {
+        self.binding = Some(KeyBinding {
+            key_sequence: key_sequence.to_string(),
+            keymap,
+        });
+        self
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn binding(&self) -> Option<&KeyBinding> {
+        self.binding.as_ref()
+    }
+
+    /// The name of the plugin's function that implements this widget.
+    pub(crate) fn function_name(&self, plugin_name: &str) -> String {
+        format!(
+            "{plugin_name}_{}_widget",
+            self.name.replace(['-', '.'], "_")
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl KeyBinding {
+    /// The key sequence, in `bindkey` notation such as `^Xg`.
+    pub(crate) fn key_sequence(&self) -> &str {
+        &self.key_sequence
+    }
+
+    pub(crate) fn keymap(&self) -> Keymap {
+        self.keymap
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Keymap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Main => "main",
+                Self::Viins => "viins",
+                Self::Vicmd => "vicmd",
+                Self::Emacs => "emacs",
+            }
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_widget_names() {
+        let widget: Widget = "fzf-history.search".parse().unwrap();
+        assert_eq!(
+            widget.function_name("demo"),
+            "demo_fzf_history_search_widget"
+        );
+        assert!(widget.binding().is_none());
+        assert!("".parse::<Widget>().is_err());
+        assert!(".accept-line".parse::<Widget>().is_err());
+        assert!("my widget".parse::<Widget>().is_err());
+    }
+}
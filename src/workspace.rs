@@ -0,0 +1,234 @@
+//! Workspaces of many plugins, managed together.
+//!
+//! A workspace is a directory containing a `zsh-plugin-workspace.toml` file that
+//! lists the directories of its plugins, relative to the workspace directory.
+//!
+//! With the `--workspace` option the `check`, `test`, `upgrade`, `doc` and `package`
+//! commands run against every plugin of the workspace found in, or above, the
+//! current directory.
+//! Each plugin is handled by a separate run of this tool, several at a time, and
+//! its output is shown once it has finished, in the order the plugins are listed,
+//! followed by a summary of the plugins that passed and failed.
+//!
+//! # Examples
+//!
+//! ```toml
+//! plugins = [
+//!     "zsh-containers-plugin",
+//!     "zsh-git-tools-plugin",
+//! ]
+//! ```
+
+use crate::error::Error;
+use flat_error::FlatError;
+use serde::Deserialize;
+use std::{
+    env::{current_dir, current_exe},
+    ffi::OsString,
+    fs::read_to_string,
+    io::{self, Write},
+    num::NonZeroUsize,
+    panic::resume_unwind,
+    path::{Path, PathBuf},
+    process::{Command, ExitCode, Output},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread::{available_parallelism, scope},
+};
+use tracing::{error, trace};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// A directory of plugins, described by its `zsh-plugin-workspace.toml` file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Workspace {
+    root: PathBuf,
+    plugins: Vec<PathBuf>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) const P_WORKSPACE: &str = "zsh-plugin-workspace.toml";
+
+/// Run this tool with `args`, and the `--plugin-dir` option, for every plugin in the
+/// workspace, and report whether each passed or failed.
+pub(crate) fn run_in_workspace(args: &[OsString]) -> Result<ExitCode, Error> {
+    trace!("run_in_workspace => args: {args:?}");
+    let workspace = Workspace::find(&current_dir()?)?;
+    let program = current_exe()?;
+    let outputs = run_all(&workspace.plugin_dirs(), |dir| {
+        Command::new(&program)
+            .args(args)
+            .arg("--plugin-dir")
+            .arg(dir)
+            .output()
+    });
+
+    let mut failed = 0;
+    let mut summary = Vec::default();
+    for (plugin, output) in workspace.plugins.iter().zip(outputs) {
+        println!("── {}", plugin.display());
+        let passed = match output {
+            Ok(Output {
+                status,
+                stdout,
+                stderr,
+            }) => {
+                io::stdout().write_all(&stdout)?;
+                io::stderr().write_all(&stderr)?;
+                status.success()
+            }
+            Err(e) => {
+                eprintln!("Could not run {program:?}; error: {e}");
+                false
+            }
+        };
+        if !passed {
+            failed += 1;
+        }
+        summary.push((plugin, passed));
+        println!();
+    }
+
+    let count = summary.len();
+    println!(
+        "Workspace '{}': {} passed, {failed} failed",
+        workspace.root.display(),
+        count - failed
+    );
+    for (i, (plugin, passed)) in summary.iter().enumerate() {
+        let branch = if i + 1 == count { "└─" } else { "├─" };
+        let outcome = if *passed { "passed" } else { "failed" };
+        println!("{branch} {}: {outcome}", plugin.display());
+    }
+
+    Ok(if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Call `run` for each of `dirs`, on as many threads as there are processors, and
+/// return the results in the order of `dirs`.
+pub(crate) fn run_all<T, F>(dirs: &[PathBuf], run: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&Path) -> T + Sync,
+{
+    let workers = available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(dirs.len());
+    let next = AtomicUsize::new(0);
+    let mut results = scope(|scope| {
+        let handles = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::default();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(dir) = dirs.get(i) else {
+                            break done;
+                        };
+                        done.push((i, run(dir)));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_else(|e| resume_unwind(e)))
+            .collect::<Vec<_>>()
+    });
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Workspace {
+    /// Load the workspace in `start`, or in the nearest of its parents that has a
+    /// workspace file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::WorkspaceNotFound`] if there is no workspace file, or
+    /// [`Error::InvalidWorkspace`] if it cannot be parsed.
+    pub(crate) fn find(start: &Path) -> Result<Self, Error> {
+        trace!("Workspace::find => start: {start:?}");
+        let root = start
+            .ancestors()
+            .find(|dir| dir.join(P_WORKSPACE).is_file())
+            .ok_or_else(|| {
+                error!("No workspace file in {start:?} or its parents");
+                Error::WorkspaceNotFound {
+                    path: start.to_path_buf(),
+                }
+            })?;
+        Self::parse(root, &read_to_string(root.join(P_WORKSPACE))?)
+    }
+
+    /// The directories of the workspace's plugins, in the order they are listed.
+    pub(crate) fn plugin_dirs(&self) -> Vec<PathBuf> {
+        self.plugins.iter().map(|p| self.root.join(p)).collect()
+    }
+
+    fn parse(root: &Path, content: &str) -> Result<Self, Error> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct WorkspaceFile {
+            plugins: Vec<PathBuf>,
+        }
+
+        let file: WorkspaceFile = toml::from_str(content).map_err(|e| {
+            let path = root.join(P_WORKSPACE);
+            error!("Error parsing workspace file {path:?}, error: {e}");
+            Error::InvalidWorkspace {
+                path,
+                source: FlatError::from_any(&e),
+            }
+        })?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            plugins: file.plugins,
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_workspace() {
+        let workspace =
+            Workspace::parse(Path::new("/ws"), "plugins = [\"zsh-a-plugin\", \"b\"]\n").unwrap();
+        assert_eq!(
+            workspace.plugin_dirs(),
+            vec![PathBuf::from("/ws/zsh-a-plugin"), PathBuf::from("/ws/b")]
+        );
+        assert!(Workspace::parse(Path::new("/ws"), "plugin = []\n").is_err());
+    }
+
+    #[test]
+    fn run_all_keeps_order() {
+        let dirs = (0..20)
+            .map(|i| PathBuf::from(i.to_string()))
+            .collect::<Vec<_>>();
+        let results = run_all(&dirs, |dir| dir.display().to_string());
+        assert_eq!(results, (0..20).map(|i| i.to_string()).collect::<Vec<_>>());
+    }
+}